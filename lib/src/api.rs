@@ -2,6 +2,8 @@
 //!
 //! The function names are provided with the Rust naming convention.
 
+use crate::glob::{Glob, GlobFilter};
+use crate::KeyPath;
 use std::cell::RefCell;
 use std::collections::BTreeSet;
 use std::iter::FromIterator;
@@ -13,9 +15,19 @@ use std::iter::FromIterator;
 /// * [`WalkTree`] and [`WalkLeaves`]
 /// * [`SectionTree`] and [`SectionLeaves`]
 ///
+/// It also provides [`walk_glob`] to select keys with wildcards.
+///
 /// You can use the blanket implementations for this trait by importing it.
 ///
+/// Each call looks at all the data and [`sub_tree`] and [`sub_leaves`] clone
+/// it first. If there are many keys or many queries, use a
+/// [`SectionIndex`](crate::SectionIndex) or [`CniRangeExt`](crate::CniRangeExt)
+/// instead.
+///
 /// [`SubTree`]: CniExt::sub_tree
+/// [`walk_glob`]: CniExt::walk_glob
+/// [`sub_tree`]: CniExt::sub_tree
+/// [`sub_leaves`]: CniExt::sub_leaves
 /// [`SubLeaves`]: CniExt::sub_leaves
 /// [`WalkTree`]: CniExt::walk_tree
 /// [`WalkLeaves`]: CniExt::walk_leaves
@@ -60,7 +72,7 @@ pub trait CniExt<V>: Sized {
     /// [`HashMap::values`]: ::std::collections::HashMap::values
     /// [`HashMap::keys`]: ::std::collections::HashMap::keys
     #[must_use]
-    fn sub_tree(&self, section: &str) -> Self
+    fn sub_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> Self
     where
         Self: Clone + FromIterator<(String, V)>;
     /// Returns a clone of self that only contains direct child elements of the
@@ -95,7 +107,7 @@ pub trait CniExt<V>: Sized {
     /// [`HashMap::values`]: ::std::collections::HashMap::values
     /// [`HashMap::keys`]: ::std::collections::HashMap::keys
     #[must_use]
-    fn sub_leaves(&self, section: &str) -> Self
+    fn sub_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> Self
     where
         Self: Clone + FromIterator<(String, V)>;
     /// Returns an iterator that only contains child elements of the
@@ -135,7 +147,7 @@ pub trait CniExt<V>: Sized {
     ///     ]
     /// );
     /// ```
-    fn walk_tree(self, section: &str) -> SectionFilter<Self::Iter>;
+    fn walk_tree<'s>(self, section: impl Into<KeyPath<'s>>) -> SectionFilter<'s, Self::Iter>;
     /// Returns an iterator that only contains direct child elements of the
     /// specified section. The section name and delimiter will be included in
     /// the result. The order is unspecified.
@@ -172,7 +184,42 @@ pub trait CniExt<V>: Sized {
     ///     ]
     /// );
     /// ```
-    fn walk_leaves(self, section: &str) -> SectionFilter<Self::Iter>;
+    fn walk_leaves<'s>(self, section: impl Into<KeyPath<'s>>) -> SectionFilter<'s, Self::Iter>;
+    /// Returns an iterator over the elements whose keys match a pattern,
+    /// with the key parts captured by the wildcards. See [`Glob`] for the
+    /// syntax of the pattern. The order is unspecified.
+    ///
+    /// # Examples
+    /// ```
+    /// use cni_format::CniExt;
+    ///
+    /// let cni = r"
+    /// [servers]
+    /// web1.host = 10.0.0.1
+    /// web1.port = 80
+    /// web2.host = 10.0.0.2
+    /// ";
+    ///
+    /// let mut hosts = cni_format::from_str(&cni)
+    ///     .expect("could not parse CNI")
+    ///     .iter()
+    ///     .walk_glob("servers.*.host")
+    ///     .map(|m| (m.capture(0).unwrap().to_string(), m.value.clone()))
+    ///     .collect::<Vec<_>>();
+    /// // because the order is unspecified, have to sort to compare
+    /// hosts.sort();
+    ///
+    /// assert_eq!(
+    ///     hosts,
+    ///     vec![
+    ///         ("web1".to_string(), "10.0.0.1".to_string()),
+    ///         ("web2".to_string(), "10.0.0.2".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`Glob`]: crate::Glob
+    fn walk_glob(self, pattern: &str) -> GlobFilter<'_, Self::Iter>;
     /// Returns the names of subsection of the specified section. Note that
     /// this does not necessarily mean that the respective section names are in
     /// the source as section headers.
@@ -204,7 +251,7 @@ pub trait CniExt<V>: Sized {
     ///     ]
     /// );
     /// ```
-    fn section_tree(&self, section: &str) -> BTreeSet<String>
+    fn section_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<String>
     where
         Self: Clone;
     /// Returns the names of direct subsections of the specified section. Note
@@ -239,7 +286,7 @@ pub trait CniExt<V>: Sized {
     ///     ]
     /// );
     /// ```
-    fn section_leaves(&self, section: &str) -> BTreeSet<String>
+    fn section_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<String>
     where
         Self: Clone;
 }
@@ -253,136 +300,117 @@ where
     type Iter = I;
 
     /// Implements the `SubTree` API function.
-    fn sub_tree(&self, section: &str) -> Self
+    fn sub_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> Self
     where
         Self: Clone + FromIterator<(String, V)>,
     {
+        let section = section.into();
         self.clone()
             .into_iter()
             .filter_map(|(k, v)| {
-                let k = k.as_ref();
-                if section.is_empty() {
-                    Some((k.to_string(), v))
-                } else if k.starts_with(section) && k[section.len()..].starts_with('.') {
-                    Some((k[section.len() + 1..].to_string(), v))
-                } else {
-                    None
-                }
+                let key = KeyPath::from(k.as_ref())
+                    .strip_prefix(&section)?
+                    .to_string();
+                Some((key, v))
             })
             .collect()
     }
 
     /// Implements the `SubLeaves` API function.
-    fn sub_leaves(&self, section: &str) -> Self
+    fn sub_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> Self
     where
         Self: Clone + FromIterator<(String, V)>,
     {
+        let section = section.into();
         self.clone()
             .into_iter()
             .filter_map(|(k, v)| {
-                let k = k.as_ref();
-                if section.is_empty() && !k.contains('.') {
-                    Some((k.to_string(), v))
-                } else if k.starts_with(section)
-                    && k[section.len()..].starts_with('.')
-                    && !k[section.len() + 1..].contains('.')
-                {
-                    Some((k[section.len() + 1..].to_string(), v))
-                } else {
+                let key = KeyPath::from(k.as_ref())
+                    .strip_prefix(&section)?
+                    .to_string();
+                if key.contains('.') {
                     None
+                } else {
+                    Some((key, v))
                 }
             })
             .collect()
     }
 
     /// Implements the `WalkTree` API function.
-    fn walk_tree(self, section: &str) -> SectionFilter<I> {
+    fn walk_tree<'s>(self, section: impl Into<KeyPath<'s>>) -> SectionFilter<'s, I> {
         SectionFilter {
             iter: RefCell::new(self.into_iter()),
-            section,
+            section: section.into(),
             only_direct_children: false,
         }
     }
 
     /// Implements the `WalkLeaves` API function.
-    fn walk_leaves(self, section: &str) -> SectionFilter<I> {
+    fn walk_leaves<'s>(self, section: impl Into<KeyPath<'s>>) -> SectionFilter<'s, I> {
         SectionFilter {
             iter: RefCell::new(self.into_iter()),
-            section,
+            section: section.into(),
             only_direct_children: true,
         }
     }
 
+    /// Filters the keys with a [`Glob`] pattern.
+    fn walk_glob(self, pattern: &str) -> GlobFilter<'_, I> {
+        GlobFilter {
+            iter: self.into_iter(),
+            glob: Glob::new(pattern),
+        }
+    }
+
     /// Implements the `SectionTree` API function.
-    fn section_tree(&self, section: &str) -> BTreeSet<String>
+    fn section_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<String>
     where
         T: Clone,
     {
-        // TODO: `keys` could be simplified if nightly feature map_first_last is
-        // stabilized, see <https://github.com/rust-lang/rust/issues/62924>
-        let mut keys = vec![];
+        let section = section.into();
         let mut result = BTreeSet::new();
-        keys.extend(
-            self.clone()
-                .walk_tree(section)
-                // ignore current section's name
-                .map(|(k, _)| {
-                    if section.is_empty() {
-                        k.as_ref().to_string()
-                    } else {
-                        k.as_ref()[section.len() + 1..].to_string()
-                    }
-                }),
-        );
-
-        while let Some(key) = keys.pop() {
-            if let Some(pos) = key.rfind('.') {
-                let section = key.split_at(pos).0.to_string();
-                if !keys.contains(&section) && !result.contains(&section) {
-                    keys.push(section.clone());
+        for (k, _) in self.clone().walk_tree(&section) {
+            let key = KeyPath::from(k.as_ref());
+            // ignore current section's name
+            let mut key = match key.strip_prefix(&section) {
+                Some(key) => KeyPath::from(key),
+                None => continue,
+            };
+            // if a section is already known, so are all its parents
+            while let Some(parent) = key.parent().filter(|parent| !parent.is_root()) {
+                if result.contains(parent.as_str()) {
+                    break;
                 }
-                result.insert(section.to_string());
+                result.insert(parent.as_str().to_string());
+                key = parent.into_owned();
             }
         }
 
         result
     }
 
-    fn section_leaves(&self, section: &str) -> BTreeSet<String>
+    fn section_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<String>
     where
         T: Clone,
     {
-        // TODO: `keys` could be simplified if nightly feature map_first_last is
-        // stabilized, see <https://github.com/rust-lang/rust/issues/62924>
-        let mut keys = vec![];
-        let mut result = BTreeSet::new();
-        keys.extend(
-            self.clone()
-                .walk_tree(section)
+        let section = section.into();
+        self.clone()
+            .walk_tree(&section)
+            .filter_map(|(k, _)| {
                 // ignore current section's name
-                .map(|(k, _)| {
-                    if section.is_empty() {
-                        k.as_ref().to_string()
-                    } else {
-                        k.as_ref()[section.len() + 1..].to_string()
-                    }
-                }),
-        );
-
-        while let Some(key) = keys.pop() {
-            if let Some(pos) = key.rfind('.') {
-                let section = key.split_at(pos).0.to_string();
-                if section.contains('.') {
-                    continue;
+                let key = KeyPath::from(k.as_ref())
+                    .strip_prefix(&section)?
+                    .to_string();
+                let key = KeyPath::from(key);
+                // the direct subsection is the first part of the remaining
+                // key, if there is more than one part
+                match key.split_first()? {
+                    (_, rest) if rest.is_root() => None,
+                    (first, _) => Some(first.to_string()),
                 }
-                if !keys.contains(&section) && !result.contains(&section) {
-                    keys.push(section.clone());
-                }
-                result.insert(section.to_string());
-            }
-        }
-
-        result
+            })
+            .collect()
     }
 }
 
@@ -397,7 +425,7 @@ where
 pub struct SectionFilter<'section, I> {
     // this has to use interior mutability because of how `next` has to be done
     iter: RefCell<I>,
-    section: &'section str,
+    section: KeyPath<'section>,
     only_direct_children: bool,
 }
 
@@ -411,10 +439,9 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         self.iter.borrow_mut().find(|(k, _)| {
             // using self inside closure requires interior mutability on iter
-            let k = k.as_ref();
-            k.starts_with(self.section)
-                && (k[self.section.len()..].starts_with('.') || self.section.is_empty())
-                && !(self.only_direct_children && k[self.section.len() + 1..].contains('.'))
+            KeyPath::from(k.as_ref())
+                .strip_prefix(&self.section)
+                .is_some_and(|rest| !(self.only_direct_children && rest.contains('.')))
         })
     }
 }
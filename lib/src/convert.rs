@@ -0,0 +1,214 @@
+//! This module supplies the conversions of values to other types that are
+//! used by [`CniTypedExt`](crate::CniTypedExt). They are also used by
+//! `serde_cni` so both accept the same representations. The `format_*`
+//! functions do the reverse.
+
+use crate::error::ValueKind;
+use std::time::Duration;
+
+/// Parses a boolean value. The comparison is case insensitive.
+///
+/// | `true`  | `false` |
+/// |---------|---------|
+/// | `1`     | `0`     |
+/// | `+`     | `-`     |
+/// | `true`  | `false` |
+/// | `yes`   | `no`    |
+/// | `on`    | `off`   |
+/// | `up`    | `down`  |
+///
+/// # Errors
+/// Returns [`ValueKind::Bool`] if the value is none of the above.
+pub fn parse_bool(value: &str) -> Result<bool, ValueKind> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "+" | "true" | "yes" | "on" | "up" => Ok(true),
+        "0" | "-" | "false" | "no" | "off" | "down" => Ok(false),
+        _ => Err(ValueKind::Bool),
+    }
+}
+
+/// Splits a value into a number and the following unit. Whitespace between
+/// the two is allowed.
+fn split_unit(value: &str) -> (&str, &str) {
+    let pos = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(pos);
+    (number, unit.trim_start())
+}
+
+/// Parses a duration that consists of one or more numbers, each followed by
+/// a unit, e.g. `30s` or `1h 30m`. The number 0 does not need a unit.
+///
+/// The supported units are `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`.
+///
+/// # Errors
+/// Returns [`ValueKind::Duration`] if the value is malformed or too large.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use cni_format::convert::parse_duration;
+///
+/// assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+/// assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+/// assert_eq!(parse_duration("1m 500ms"), Ok(Duration::from_millis(60_500)));
+/// ```
+pub fn parse_duration(value: &str) -> Result<Duration, ValueKind> {
+    let mut rest = value.trim();
+    if rest == "0" {
+        return Ok(Duration::ZERO);
+    } else if rest.is_empty() {
+        return Err(ValueKind::Duration);
+    }
+
+    // count in nanoseconds, which is the resolution of Duration
+    let mut nanos: u128 = 0;
+    while !rest.is_empty() {
+        let (number, unit) = split_unit(rest);
+        let number = number.parse::<u128>().map_err(|_| ValueKind::Duration)?;
+
+        // the longest unit has to come first so e.g. "ms" is not read as "m"
+        let &(name, factor) = [
+            ("ns", 1),
+            ("us", 1_000),
+            ("µs", 1_000),
+            ("ms", 1_000_000),
+            ("s", 1_000_000_000),
+            ("m", 60 * 1_000_000_000),
+            ("h", 60 * 60 * 1_000_000_000),
+            ("d", 24 * 60 * 60 * 1_000_000_000),
+        ]
+        .iter()
+        .find(|(name, _)| unit.starts_with(name))
+        .ok_or(ValueKind::Duration)?;
+
+        nanos = number
+            .checked_mul(factor)
+            .and_then(|part| nanos.checked_add(part))
+            .ok_or(ValueKind::Duration)?;
+        rest = unit[name.len()..].trim_start();
+    }
+
+    let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| ValueKind::Duration)?;
+    // the remainder is always less than 10^9 so this can not fail
+    let nanos = u32::try_from(nanos % 1_000_000_000).unwrap_or_default();
+    Ok(Duration::new(secs, nanos))
+}
+
+/// Formats a duration so it can be read by [`parse_duration`], using the
+/// largest units possible, e.g. `1h30m`.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use cni_format::convert::format_duration;
+///
+/// assert_eq!(format_duration(Duration::from_secs(90 * 60)), "1h30m");
+/// assert_eq!(format_duration(Duration::from_millis(60_500)), "1m500ms");
+/// assert_eq!(format_duration(Duration::ZERO), "0");
+/// ```
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0".to_string();
+    }
+
+    let mut buf = String::new();
+    for (name, factor) in [
+        ("d", 24 * 60 * 60 * 1_000_000_000),
+        ("h", 60 * 60 * 1_000_000_000),
+        ("m", 60 * 1_000_000_000),
+        ("s", 1_000_000_000),
+        ("ms", 1_000_000),
+        ("us", 1_000),
+        ("ns", 1),
+    ] {
+        if nanos >= factor {
+            buf.push_str(&(nanos / factor).to_string());
+            buf.push_str(name);
+            nanos %= factor;
+        }
+    }
+    buf
+}
+
+/// Parses a size in bytes, consisting of a number optionally followed by a
+/// unit, e.g. `4096`, `512KiB` or `10 MB`. The unit is case insensitive.
+///
+/// Decimal units (`k`, `M`, `G`, `T`, optionally followed by `B`) are
+/// multiples of 1000, binary units (`KiB`, `MiB`, `GiB`, `TiB`, the `B` is
+/// optional) are multiples of 1024. `B` alone means bytes.
+///
+/// # Errors
+/// Returns [`ValueKind::Size`] if the value is malformed or too large.
+///
+/// # Examples
+/// ```
+/// use cni_format::convert::parse_size;
+///
+/// assert_eq!(parse_size("4096"), Ok(4096));
+/// assert_eq!(parse_size("4MiB"), Ok(4 * 1024 * 1024));
+/// assert_eq!(parse_size("10 kB"), Ok(10_000));
+/// ```
+pub fn parse_size(value: &str) -> Result<u64, ValueKind> {
+    let (number, unit) = split_unit(value.trim());
+    let number = number.parse::<u64>().map_err(|_| ValueKind::Size)?;
+
+    let factor: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "ki" | "kib" => 1 << 10,
+        "m" | "mb" => 1000 * 1000,
+        "mi" | "mib" => 1 << 20,
+        "g" | "gb" => 1000 * 1000 * 1000,
+        "gi" | "gib" => 1 << 30,
+        "t" | "tb" => 1000 * 1000 * 1000 * 1000,
+        "ti" | "tib" => 1 << 40,
+        _ => return Err(ValueKind::Size),
+    };
+
+    number.checked_mul(factor).ok_or(ValueKind::Size)
+}
+
+/// Formats a size in bytes so it can be read by [`parse_size`], using the
+/// largest binary unit that the size is a multiple of.
+///
+/// # Examples
+/// ```
+/// use cni_format::convert::format_size;
+///
+/// assert_eq!(format_size(512 * 1024), "512KiB");
+/// assert_eq!(format_size(1500), "1500");
+/// ```
+#[must_use]
+pub fn format_size(size: u64) -> String {
+    let unit = [("TiB", 40), ("GiB", 30), ("MiB", 20), ("KiB", 10)]
+        .iter()
+        .find(|(_, shift)| size != 0 && size.trailing_zeros() >= *shift);
+
+    match unit {
+        Some((name, shift)) => format!("{}{}", size >> shift, name),
+        None => size.to_string(),
+    }
+}
+
+/// Splits a value into a list at the given separator. Whitespace around
+/// each element is removed. An empty value is an empty list.
+///
+/// # Examples
+/// ```
+/// use cni_format::convert::split_list;
+///
+/// assert_eq!(split_list("a, b ,c", ','), vec!["a", "b", "c"]);
+/// assert!(split_list("", ',').is_empty());
+/// ```
+#[must_use]
+pub fn split_list(value: &str, sep: char) -> Vec<&str> {
+    if value.trim().is_empty() {
+        Vec::new()
+    } else {
+        value.split(sep).map(str::trim).collect()
+    }
+}
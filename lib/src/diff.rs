@@ -0,0 +1,155 @@
+//! This module supplies a comparison of two sets of CNI data by keys, which
+//! ignores the order of the keys and the formatting of the source.
+
+use crate::KeyPath;
+use std::collections::BTreeMap;
+
+/// The type of a [`Change`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The key only exists in the new data.
+    Added,
+    /// The key only exists in the old data.
+    Removed,
+    /// The key exists in both, but the values are different.
+    Changed,
+}
+
+/// A difference between two sets of CNI data, see [`diff`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Change<'a, V> {
+    /// The full key that is different.
+    pub key: &'a str,
+    /// The value in the old data, if the key exists there.
+    pub old: Option<&'a V>,
+    /// The value in the new data, if the key exists there.
+    pub new: Option<&'a V>,
+}
+
+// implemented manually because deriving would require `V: Clone`
+impl<V> Clone for Change<'_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for Change<'_, V> {}
+
+impl<'a, V> Change<'a, V> {
+    /// Returns whether the key was added, removed or changed.
+    #[must_use]
+    pub fn kind(&self) -> ChangeKind {
+        match (self.old, self.new) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            (Some(_), Some(_)) => ChangeKind::Changed,
+        }
+    }
+
+    /// Returns the section the key is in, which is empty for the top level
+    /// section.
+    #[must_use]
+    pub fn section(&self) -> &'a str {
+        let len = KeyPath::from(self.key)
+            .parent()
+            .map_or(0, |parent| parent.as_str().len());
+        &self.key[..len]
+    }
+}
+
+/// Compares two sets of CNI data and returns the keys that were added,
+/// removed or have a different value. Keys in the same section are next to
+/// each other in the result, see [`diff_by`].
+///
+/// # Examples
+/// ```
+/// use cni_format::ChangeKind;
+///
+/// let old = cni_format::from_str("a = 1\nb = 2\n[s]\nc = 3\n").expect("could not parse CNI");
+/// let new = cni_format::from_str("[s]\nc = 4\nd = 5\n").expect("could not parse CNI");
+///
+/// let changes = cni_format::diff(&old, &new);
+/// let changes = changes
+///     .iter()
+///     .map(|change| (change.kind(), change.key))
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     changes,
+///     vec![
+///         (ChangeKind::Removed, "a"),
+///         (ChangeKind::Removed, "b"),
+///         (ChangeKind::Changed, "s.c"),
+///         (ChangeKind::Added, "s.d"),
+///     ]
+/// );
+/// ```
+pub fn diff<'a, I, J, K, V>(old: I, new: J) -> Vec<Change<'a, V>>
+where
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+    J: IntoIterator<Item = (&'a K, &'a V)>,
+    K: AsRef<str> + ?Sized + 'a,
+    V: PartialEq + 'a,
+{
+    diff_by(old, new, PartialEq::eq)
+}
+
+/// Compares two sets of CNI data like [`diff`], using a function to decide if
+/// two values are equal. This allows values to carry additional data, like
+/// the position they were read from, that is not compared.
+///
+/// Keys are sorted part by part, so that keys without a section come first
+/// and all keys of a section are next to each other.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+///
+/// // values with the line they were read from
+/// let mut old = HashMap::new();
+/// old.insert("a", ("1", 1));
+/// let mut new = HashMap::new();
+/// new.insert("a", ("1", 3));
+///
+/// assert!(cni_format::diff_by(&old, &new, |x, y| x.0 == y.0).is_empty());
+/// assert_eq!(cni_format::diff(&old, &new).len(), 1);
+/// ```
+pub fn diff_by<'a, I, J, K, V, F>(old: I, new: J, mut eq: F) -> Vec<Change<'a, V>>
+where
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+    J: IntoIterator<Item = (&'a K, &'a V)>,
+    K: AsRef<str> + ?Sized + 'a,
+    V: 'a,
+    F: FnMut(&V, &V) -> bool,
+{
+    let mut keys = BTreeMap::<&str, (Option<&V>, Option<&V>)>::new();
+    for (key, value) in old {
+        keys.entry(key.as_ref()).or_default().0 = Some(value);
+    }
+    for (key, value) in new {
+        keys.entry(key.as_ref()).or_default().1 = Some(value);
+    }
+
+    let mut changes = keys
+        .into_iter()
+        .filter(|(_, values)| match values {
+            (Some(old), Some(new)) => !eq(old, new),
+            _ => true,
+        })
+        .map(|(key, (old, new))| Change { key, old, new })
+        .collect::<Vec<_>>();
+    changes.sort_by(|a, b| {
+        let (a, b) = (KeyPath::from(a.key), KeyPath::from(b.key));
+        (a.depth() > 1)
+            .cmp(&(b.depth() > 1))
+            .then_with(|| {
+                let (a, b) = (
+                    a.parent().unwrap_or_default(),
+                    b.parent().unwrap_or_default(),
+                );
+                a.segments().cmp(b.segments())
+            })
+            .then_with(|| a.segments().cmp(b.segments()))
+    });
+    changes
+}
@@ -0,0 +1,147 @@
+//! This module supplies functions to overlay environment variables onto a
+//! key/value store, e.g. to override values from a configuration file.
+//!
+//! An environment variable like `APP_SERVER__PORT` is mapped to the key
+//! `server.port` if the prefix is `APP_` and the separator is `__`.
+
+use std::ffi::OsString;
+
+/// How the case of environment variable names is changed when turning them
+/// into keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Case {
+    /// Keep the name as it is.
+    Preserve,
+    /// Convert the name to lower case.
+    Lower,
+    /// Convert the name to upper case.
+    Upper,
+}
+
+/// A struct to pass options for mapping environment variable names to keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvOpts {
+    /// Only environment variables starting with this prefix are used, the
+    /// prefix is removed from the resulting key. Default: empty
+    pub prefix: String,
+    /// The part of an environment variable name that separates sections.
+    /// It will be replaced with a dot. Default: `__`
+    pub separator: String,
+    /// How the case of the environment variable name is changed.
+    /// Default: [`Case::Lower`]
+    pub case: Case,
+}
+
+impl Default for EnvOpts {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            separator: "__".to_string(),
+            case: Case::Lower,
+        }
+    }
+}
+
+impl EnvOpts {
+    /// Creates options with the given prefix and defaults for everything else.
+    #[must_use]
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Turns the name of an environment variable into a key.
+    ///
+    /// Returns `None` if the name does not start with the prefix or if the
+    /// resulting key would not be a valid key, e.g. because it has an empty
+    /// part like `server..port`.
+    ///
+    /// # Examples
+    /// ```
+    /// use cni_format::EnvOpts;
+    ///
+    /// let opts = EnvOpts::with_prefix("APP_");
+    ///
+    /// assert_eq!(opts.key("APP_SERVER__PORT"), Some("server.port".to_string()));
+    /// assert_eq!(opts.key("APP_LOG_LEVEL"), Some("log_level".to_string()));
+    /// assert_eq!(opts.key("HOME"), None);
+    /// ```
+    #[must_use]
+    pub fn key(&self, name: &str) -> Option<String> {
+        let name = name.strip_prefix(&self.prefix)?;
+        let key = if self.separator.is_empty() {
+            name.to_string()
+        } else {
+            name.replace(&self.separator, ".")
+        };
+        let key = match self.case {
+            Case::Preserve => key,
+            Case::Lower => key.to_lowercase(),
+            Case::Upper => key.to_uppercase(),
+        };
+
+        // use the most permissive rules so keys from the `more-keys`
+        // extension are possible
+        let opts = crate::Opts {
+            ini: false,
+            more_keys: true,
+        };
+        crate::KeyPath::from(&key).validate(opts).ok()?;
+        // a separator at the start or end of a part is most likely a mistake
+        if key.split('.').any(str::is_empty) {
+            return None;
+        }
+        Some(key)
+    }
+
+    /// Maps the given environment variables to key/value pairs. Variables
+    /// that do not match the options are skipped.
+    ///
+    /// This is useful if the environment variables do not come from the
+    /// environment of the current process, otherwise see [`EnvOpts::vars`].
+    pub fn map_vars<'a, I, K, V>(&'a self, vars: I) -> impl Iterator<Item = (String, String)> + 'a
+    where
+        I: IntoIterator<Item = (K, V)>,
+        I::IntoIter: 'a,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        vars.into_iter()
+            .filter_map(move |(name, value)| Some((self.key(name.as_ref())?, value.into())))
+    }
+
+    /// Returns the key/value pairs from the environment of the current
+    /// process that match these options. Variables that are not valid
+    /// unicode are skipped.
+    pub fn vars(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        self.map_vars(
+            std::env::vars_os().filter_map(|(name, value): (OsString, OsString)| {
+                Some((name.into_string().ok()?, value.into_string().ok()?))
+            }),
+        )
+    }
+}
+
+/// Overlays the environment variables of the current process onto the given
+/// key/value store. Values from the environment replace existing values.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+/// use cni_format::EnvOpts;
+///
+/// let mut map: HashMap<String, String> = cni_format::from_str("[server]\nport = 80\n").unwrap();
+///
+/// std::env::set_var("DOCTEST_SERVER__PORT", "8080");
+/// cni_format::overlay_env(&mut map, &EnvOpts::with_prefix("DOCTEST_"));
+///
+/// assert_eq!(map["server.port"], "8080");
+/// ```
+pub fn overlay_env<M>(map: &mut M, opts: &EnvOpts)
+where
+    M: Extend<(String, String)>,
+{
+    map.extend(opts.vars());
+}
@@ -49,3 +49,78 @@ impl std::fmt::Display for Kind {
         )
     }
 }
+
+/// An error that occurred while converting a value to a specific type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueError {
+    /// The key of the value that could not be converted.
+    pub key: String,
+    /// Line and column of the value (both starting at 1), if known. See
+    /// [`CniParser::last_pos`](crate::CniParser::last_pos).
+    pub pos: Option<(usize, usize)>,
+    /// The type of error that occured.
+    pub kind: ValueKind,
+}
+
+impl ValueError {
+    /// Sets the position of the value, e.g. from
+    /// [`CniParser::last_pos`](crate::CniParser::last_pos).
+    #[must_use]
+    pub fn with_pos(self, line: usize, col: usize) -> Self {
+        Self {
+            pos: Some((line, col)),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((line, col)) = self.pos {
+            write!(f, "line {line}:{col}: ")?;
+        }
+        write!(f, "{}: {}", self.key, self.kind)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+/// A type of error that may occur while converting a value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueKind {
+    /// The value is not a boolean.
+    Bool,
+    /// The value is not a duration.
+    Duration,
+    /// The value is not a size.
+    Size,
+    /// The value could not be parsed, contains the message of the parser.
+    Parse(String),
+}
+
+impl std::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool => write!(f, "malformed boolean"),
+            Self::Duration => write!(f, "malformed duration"),
+            Self::Size => write!(f, "malformed size"),
+            Self::Parse(msg) => f.write_str(msg),
+        }
+    }
+}
+
+/// An error that occurred while serializing, because a key can not be
+/// written in CNI format with the selected options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyError {
+    /// The key that can not be written.
+    pub key: String,
+}
+
+impl std::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid key '{}'", self.key)
+    }
+}
+
+impl std::error::Error for KeyError {}
@@ -0,0 +1,209 @@
+//! This module supplies pattern queries over keys, see
+//! [`CniExt::walk_glob`](crate::CniExt::walk_glob).
+
+use crate::KeyPath;
+use std::ops::Range;
+
+/// A part of a pattern between two dots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part<'p> {
+    /// Matches exactly this key part.
+    Literal(&'p str),
+    /// `*`: matches any one key part.
+    Any,
+    /// `**`: matches any number of key parts, including none.
+    AnyDepth,
+}
+
+/// A pattern for dotted keys. The pattern is split at dots like a key and
+/// each part is one of:
+/// * `*`, which matches exactly one part of the key,
+/// * `**`, which matches any number of parts of the key, including none,
+/// * anything else, which has to be equal to the part of the key.
+///
+/// Wildcards only match whole parts, so `web*` only matches a key part that
+/// is literally `web*`. Each wildcard captures the part or parts of the key
+/// that it matched. If a `**` could match different numbers of parts, it
+/// matches as few as possible.
+///
+/// # Examples
+/// ```
+/// use cni_format::Glob;
+///
+/// let glob = Glob::new("servers.*.host");
+/// assert!(glob.is_match("servers.web1.host"));
+/// assert!(!glob.is_match("servers.web1.port"));
+/// assert!(!glob.is_match("servers.eu.web1.host"));
+///
+/// let glob = Glob::new("servers.**.host");
+/// assert!(glob.is_match("servers.host"));
+/// assert!(glob.is_match("servers.eu.web1.host"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Glob<'p> {
+    parts: Vec<Part<'p>>,
+}
+
+impl<'p> Glob<'p> {
+    /// Parses a pattern. Every string is a valid pattern, the empty pattern
+    /// only matches the empty key.
+    #[must_use]
+    pub fn new(pattern: &'p str) -> Self {
+        let parts = if pattern.is_empty() {
+            Vec::new()
+        } else {
+            pattern
+                .split('.')
+                .map(|part| match part {
+                    "*" => Part::Any,
+                    "**" => Part::AnyDepth,
+                    _ => Part::Literal(part),
+                })
+                .collect()
+        };
+        Self { parts }
+    }
+
+    /// Returns `true` if the key matches the pattern.
+    #[must_use]
+    pub fn is_match(&self, key: &str) -> bool {
+        self.captures(key).is_some()
+    }
+
+    /// Returns the positions of the captured key parts in the key if it
+    /// matches the pattern, one for each wildcard in the pattern.
+    #[must_use]
+    pub fn captures(&self, key: &str) -> Option<Vec<Range<usize>>> {
+        let mut start = 0;
+        let segments = KeyPath::from(key)
+            .segments()
+            .map(|part| {
+                let segment = start..start + part.len();
+                // skip the dot
+                start = segment.end + 1;
+                segment
+            })
+            .collect::<Vec<_>>();
+
+        let mut captures = Vec::new();
+        if match_parts(key, &self.parts, &segments, &mut captures) {
+            Some(captures)
+        } else {
+            None
+        }
+    }
+}
+
+/// Matches the remaining pattern against the remaining key parts, with
+/// backtracking for wildcards.
+fn match_parts(
+    key: &str,
+    parts: &[Part<'_>],
+    segments: &[Range<usize>],
+    captures: &mut Vec<Range<usize>>,
+) -> bool {
+    let Some((part, rest)) = parts.split_first() else {
+        return segments.is_empty();
+    };
+
+    match part {
+        Part::Literal(literal) => {
+            segments
+                .first()
+                .is_some_and(|segment| &key[segment.clone()] == *literal)
+                && match_parts(key, rest, &segments[1..], captures)
+        }
+        Part::Any => {
+            let Some(segment) = segments.first() else {
+                return false;
+            };
+            captures.push(segment.clone());
+            if match_parts(key, rest, &segments[1..], captures) {
+                return true;
+            }
+            captures.pop();
+            false
+        }
+        Part::AnyDepth => {
+            // an empty match is located where the next key part starts
+            let start = segments.first().map_or(key.len(), |segment| segment.start);
+            for n in 0..=segments.len() {
+                let end = if n == 0 { start } else { segments[n - 1].end };
+                captures.push(start..end);
+                if match_parts(key, rest, &segments[n..], captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+    }
+}
+
+/// A key/value pair that matched a [`Glob`], with the key parts captured by
+/// the wildcards.
+///
+/// This `struct` is produced by [`GlobFilter`], which is created by
+/// [`CniExt::walk_glob`](crate::CniExt::walk_glob).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlobMatch<K, V> {
+    /// The full key.
+    pub key: K,
+    /// The value.
+    pub value: V,
+    captures: Vec<Range<usize>>,
+}
+
+impl<K: AsRef<str>, V> GlobMatch<K, V> {
+    /// Returns the key parts captured by the wildcard with the specified
+    /// index, counting from 0. Parts captured by `**` are joined with dots
+    /// and may be empty.
+    #[must_use]
+    pub fn capture(&self, index: usize) -> Option<&str> {
+        let range = self.captures.get(index)?;
+        Some(&self.key.as_ref()[range.clone()])
+    }
+
+    /// Returns an iterator over the captures of all wildcards, see
+    /// [`GlobMatch::capture`].
+    pub fn captures(&self) -> impl Iterator<Item = &str> {
+        let key = self.key.as_ref();
+        self.captures.iter().map(move |range| &key[range.clone()])
+    }
+
+    /// Returns the key and the value, dropping the captures.
+    #[must_use]
+    pub fn into_pair(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+/// An iterator that filters the elements of a key-value iterator for keys
+/// that match a [`Glob`].
+///
+/// This `struct` is created by the [`walk_glob`](crate::CniExt::walk_glob)
+/// method on [`CniExt`](crate::CniExt). See its documentation for more.
+pub struct GlobFilter<'p, I> {
+    pub(crate) iter: I,
+    pub(crate) glob: Glob<'p>,
+}
+
+impl<I, K, V> Iterator for GlobFilter<'_, I>
+where
+    I: Iterator<Item = (K, V)>,
+    K: AsRef<str>,
+{
+    type Item = GlobMatch<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let glob = &self.glob;
+        self.iter.find_map(|(key, value)| {
+            let captures = glob.captures(key.as_ref())?;
+            Some(GlobMatch {
+                key,
+                value,
+                captures,
+            })
+        })
+    }
+}
@@ -0,0 +1,662 @@
+//! This module supplies faster implementations of the API functions for
+//! large amounts of data.
+//!
+//! The functions of [`CniExt`](crate::CniExt) have to look at every key for
+//! each query. [`SectionIndex`] is built once and then answers queries in
+//! time proportional to the result. A `BTreeMap` is already sorted, so
+//! [`CniRangeExt`] uses range queries on it instead of building an index.
+
+use crate::KeyPath;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::iter::FromIterator;
+use std::ops::Bound;
+
+/// A prefix tree over the dot separated parts of the keys of some CNI data,
+/// which borrows the keys and values.
+///
+/// The results of the queries are in order of the key parts, so that all
+/// keys of a section are next to each other.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeSet;
+/// use cni_format::SectionIndex;
+///
+/// let cni = r"
+/// [section]
+/// key = value
+/// subsection.key = other value
+/// [otherSection]
+/// key = value
+/// ";
+///
+/// let parsed = cni_format::from_str(&cni).expect("could not parse CNI");
+/// let index = SectionIndex::new(&parsed);
+///
+/// assert_eq!(
+///     index.sub_tree::<Vec<_>>("section"),
+///     vec![("key", &"value".to_string()), ("subsection.key", &"other value".to_string())]
+/// );
+/// assert_eq!(
+///     index.section_leaves(""),
+///     vec!["otherSection", "section"].into_iter().collect::<BTreeSet<_>>()
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SectionIndex<'a, V> {
+    root: Node<'a, V>,
+}
+
+/// A key or section in the index.
+#[derive(Clone, Debug)]
+struct Node<'a, V> {
+    /// The full key of this node.
+    path: &'a str,
+    /// The value if there is a key with this name.
+    value: Option<&'a V>,
+    /// The next key parts.
+    children: BTreeMap<&'a str, Node<'a, V>>,
+    /// The number of keys below this node.
+    len: usize,
+}
+
+impl<'a, V> Node<'a, V> {
+    fn new(path: &'a str) -> Self {
+        Self {
+            path,
+            value: None,
+            children: BTreeMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the node of a key relative to this node.
+    fn find(&self, key: &KeyPath<'_>) -> Option<&Self> {
+        key.segments()
+            .try_fold(self, |node, part| node.children.get(part))
+    }
+
+    /// Returns the key of this node relative to a section that ends at
+    /// `offset`.
+    fn relative(&self, offset: usize) -> &'a str {
+        &self.path[offset..]
+    }
+}
+
+/// Iterates over all nodes below a node in depth first order.
+struct Descendants<'i, 'a, V> {
+    stack: Vec<&'i Node<'a, V>>,
+}
+
+impl<'i, 'a, V> Descendants<'i, 'a, V> {
+    fn new(node: Option<&'i Node<'a, V>>) -> Self {
+        Self {
+            stack: node
+                .map(|node| node.children.values().rev().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl<'i, 'a, V> Iterator for Descendants<'i, 'a, V> {
+    type Item = &'i Node<'a, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.values().rev());
+        Some(node)
+    }
+}
+
+/// Returns the length of the prefix to remove from keys in the section.
+fn offset(section: &KeyPath<'_>) -> usize {
+    if section.is_root() {
+        0
+    } else {
+        section.as_str().len() + 1
+    }
+}
+
+impl<'a, V> SectionIndex<'a, V> {
+    /// Builds the index from borrowed key/value pairs, for example from
+    /// iterating over a `HashMap`. If a key occurs more than once, the last
+    /// value is used.
+    pub fn new<I, K>(data: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+        K: AsRef<str> + ?Sized + 'a,
+        V: 'a,
+    {
+        let mut index = Self {
+            root: Node::new(""),
+        };
+        for (key, value) in data {
+            index.insert(key.as_ref(), value);
+        }
+        index
+    }
+
+    fn insert(&mut self, key: &'a str, value: &'a V) {
+        let mut node = &mut self.root;
+        let mut end = 0;
+        for part in key.split('.') {
+            end += part.len();
+            node = node
+                .children
+                .entry(part)
+                .or_insert_with(|| Node::new(&key[..end]));
+            // skip the dot
+            end += 1;
+        }
+        if node.value.replace(value).is_some() {
+            return;
+        }
+
+        // count the new key in all sections it is in
+        let mut node = &mut self.root;
+        for part in key.split('.') {
+            node.len += 1;
+            node = node
+                .children
+                .get_mut(part)
+                .expect("node was inserted above");
+        }
+    }
+
+    /// Returns the number of keys in the index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.root.len
+    }
+
+    /// Returns `true` if the index contains no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.root.len == 0
+    }
+
+    /// Returns the value of a key.
+    #[must_use]
+    pub fn get<'k>(&self, key: impl Into<KeyPath<'k>>) -> Option<&'a V> {
+        self.node(&key.into())?.value
+    }
+
+    /// Returns a view of all the data.
+    #[must_use]
+    pub fn root(&self) -> SectionView<'_, 'a, V> {
+        SectionView {
+            node: &self.root,
+            offset: 0,
+        }
+    }
+
+    /// Returns a view of a section, or `None` if there are no keys in it.
+    /// The section name can contain dots to get a subsection.
+    ///
+    /// # Examples
+    /// ```
+    /// use cni_format::SectionIndex;
+    ///
+    /// let cni = r"
+    /// [server]
+    /// host = localhost
+    /// tls.cert = server.pem
+    /// ";
+    /// let parsed = cni_format::from_str(&cni).expect("could not parse CNI");
+    /// let index = SectionIndex::new(&parsed);
+    ///
+    /// let server = index.view("server").expect("no server section");
+    /// assert_eq!(server.len(), 2);
+    /// assert_eq!(server.get("host").map(String::as_str), Some("localhost"));
+    /// assert_eq!(server.keys().collect::<Vec<_>>(), vec!["host", "tls.cert"]);
+    ///
+    /// let tls = server.view("tls").expect("no tls section");
+    /// assert_eq!(tls.name(), "server.tls");
+    /// assert_eq!(tls.get("cert").map(String::as_str), Some("server.pem"));
+    ///
+    /// assert!(index.view("client").is_none());
+    /// ```
+    #[must_use]
+    pub fn view<'s>(&self, section: impl Into<KeyPath<'s>>) -> Option<SectionView<'_, 'a, V>> {
+        self.root().view(section)
+    }
+
+    fn node(&self, section: &KeyPath<'_>) -> Option<&Node<'a, V>> {
+        self.root.find(section)
+    }
+
+    /// Returns the keys of a section and everything below it, without the
+    /// keys of the section.
+    fn tree(&self, section: &KeyPath<'_>) -> impl Iterator<Item = &Node<'a, V>> {
+        Descendants::new(self.node(section)).filter(|node| node.value.is_some())
+    }
+
+    /// Returns the direct children of a section that have a value.
+    fn leaves(&self, section: &KeyPath<'_>) -> impl Iterator<Item = &Node<'a, V>> {
+        self.node(section)
+            .into_iter()
+            .flat_map(|node| node.children.values())
+            .filter(|node| node.value.is_some())
+    }
+
+    /// Returns all keys below the specified section. The section name and
+    /// delimiter will be removed in the result.
+    ///
+    /// The CNI specification calls this `SubTree`, see
+    /// [`CniExt::sub_tree`](crate::CniExt::sub_tree).
+    #[must_use]
+    pub fn sub_tree<'s, B>(&self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+    {
+        let section = section.into();
+        let offset = offset(&section);
+        self.tree(&section)
+            .filter_map(|node| Some((node.relative(offset), node.value?)))
+            .collect()
+    }
+
+    /// Returns the keys directly in the specified section. The section name
+    /// and delimiter will be removed in the result.
+    ///
+    /// The CNI specification calls this `SubLeaves`, see
+    /// [`CniExt::sub_leaves`](crate::CniExt::sub_leaves).
+    #[must_use]
+    pub fn sub_leaves<'s, B>(&self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+    {
+        let section = section.into();
+        let offset = offset(&section);
+        self.leaves(&section)
+            .filter_map(|node| Some((node.relative(offset), node.value?)))
+            .collect()
+    }
+
+    /// Returns all keys below the specified section. The section name and
+    /// delimiter will be included in the result.
+    ///
+    /// The CNI specification calls this `WalkTree`, see
+    /// [`CniExt::walk_tree`](crate::CniExt::walk_tree).
+    #[must_use]
+    pub fn walk_tree<'s, B>(&self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+    {
+        let section = section.into();
+        self.tree(&section)
+            .filter_map(|node| Some((node.path, node.value?)))
+            .collect()
+    }
+
+    /// Returns the keys directly in the specified section. The section name
+    /// and delimiter will be included in the result.
+    ///
+    /// The CNI specification calls this `WalkLeaves`, see
+    /// [`CniExt::walk_leaves`](crate::CniExt::walk_leaves).
+    #[must_use]
+    pub fn walk_leaves<'s, B>(&self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+    {
+        let section = section.into();
+        self.leaves(&section)
+            .filter_map(|node| Some((node.path, node.value?)))
+            .collect()
+    }
+
+    /// Returns the names of all subsections of the specified section.
+    ///
+    /// The CNI specification calls this `SectionTree`, see
+    /// [`CniExt::section_tree`](crate::CniExt::section_tree).
+    #[must_use]
+    pub fn section_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<&'a str> {
+        let section = section.into();
+        let offset = offset(&section);
+        Descendants::new(self.node(&section))
+            .filter(|node| !node.children.is_empty())
+            .map(|node| node.relative(offset))
+            .collect()
+    }
+
+    /// Returns the names of the direct subsections of the specified section.
+    ///
+    /// The CNI specification calls this `SectionLeaves`, see
+    /// [`CniExt::section_leaves`](crate::CniExt::section_leaves).
+    #[must_use]
+    pub fn section_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<&'a str> {
+        let section = section.into();
+        let offset = offset(&section);
+        self.node(&section)
+            .into_iter()
+            .flat_map(|node| node.children.values())
+            .filter(|node| !node.children.is_empty())
+            .map(|node| node.relative(offset))
+            .collect()
+    }
+}
+
+/// A section of a [`SectionIndex`], which borrows the index instead of
+/// copying the keys and values in the section. The keys are relative to the
+/// section, without the section name and delimiter.
+///
+/// See [`SectionIndex::view`] for an example.
+pub struct SectionView<'i, 'a, V> {
+    node: &'i Node<'a, V>,
+    /// The length of the section name and the delimiter.
+    offset: usize,
+}
+
+// implemented manually because deriving would require `V: Clone`
+impl<V> Clone for SectionView<'_, '_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for SectionView<'_, '_, V> {}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for SectionView<'_, '_, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(*self).finish()
+    }
+}
+
+impl<'i, 'a, V> SectionView<'i, 'a, V> {
+    /// Returns the full name of the section, which is empty for the top
+    /// level section.
+    #[must_use]
+    pub fn name(&self) -> &'a str {
+        self.node.path
+    }
+
+    /// Returns the number of keys in the section and its subsections.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.node.len
+    }
+
+    /// Returns `true` if there are no keys in the section.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.node.len == 0
+    }
+
+    /// Returns the value of a key relative to the section.
+    #[must_use]
+    pub fn get<'k>(&self, key: impl Into<KeyPath<'k>>) -> Option<&'a V> {
+        let key = key.into();
+        if key.is_root() {
+            return None;
+        }
+        self.node.find(&key)?.value
+    }
+
+    /// Returns a view of a subsection, or `None` if there are no keys in it.
+    #[must_use]
+    pub fn view<'s>(&self, section: impl Into<KeyPath<'s>>) -> Option<SectionView<'i, 'a, V>> {
+        let node = self.node.find(&section.into())?;
+        if node.len == 0 {
+            return None;
+        }
+        Some(SectionView {
+            node,
+            offset: offset(&KeyPath::from(node.path)),
+        })
+    }
+
+    /// Returns an iterator over the keys and values in the section and its
+    /// subsections, in the order of the key parts.
+    #[must_use]
+    pub fn iter(&self) -> SectionIter<'i, 'a, V> {
+        SectionIter {
+            nodes: Descendants::new(Some(self.node)),
+            offset: self.offset,
+        }
+    }
+
+    /// Returns an iterator over the keys in the section and its
+    /// subsections, in the order of the key parts.
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> + 'i {
+        self.iter().map(|(key, _)| key)
+    }
+}
+
+impl<'i, 'a, V> IntoIterator for SectionView<'i, 'a, V> {
+    type Item = (&'a str, &'a V);
+    type IntoIter = SectionIter<'i, 'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'i, 'a, V> IntoIterator for &SectionView<'i, 'a, V> {
+    type Item = (&'a str, &'a V);
+    type IntoIter = SectionIter<'i, 'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys and values of a [`SectionView`], with keys
+/// relative to the section.
+///
+/// This `struct` is created by the [`iter`](SectionView::iter) method on
+/// [`SectionView`].
+pub struct SectionIter<'i, 'a, V> {
+    nodes: Descendants<'i, 'a, V>,
+    offset: usize,
+}
+
+impl<'a, V> Iterator for SectionIter<'_, 'a, V> {
+    type Item = (&'a str, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        self.nodes
+            .find_map(|node| Some((node.relative(offset), node.value?)))
+    }
+}
+
+/// Provides the API functions for a `BTreeMap` by using range queries, so
+/// only the requested section is looked at and the map is not cloned.
+///
+/// The methods have the same meaning as the ones of [`SectionIndex`], but
+/// the results are in the order of the map.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeMap;
+/// use cni_format::CniRangeExt;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert("section.key".to_string(), "value");
+/// map.insert("section.subsection.key".to_string(), "other value");
+/// map.insert("otherSection.key".to_string(), "value");
+///
+/// assert_eq!(
+///     map.range_sub_leaves::<Vec<_>>("section"),
+///     vec![("key", &"value")]
+/// );
+/// assert_eq!(
+///     map.range_section_tree("").into_iter().collect::<Vec<_>>(),
+///     vec!["otherSection", "section", "section.subsection"]
+/// );
+/// ```
+pub trait CniRangeExt<V> {
+    /// Returns all keys below the specified section without the section
+    /// name, see [`SectionIndex::sub_tree`].
+    #[must_use]
+    fn range_sub_tree<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a;
+    /// Returns the keys directly in the specified section without the
+    /// section name, see [`SectionIndex::sub_leaves`].
+    #[must_use]
+    fn range_sub_leaves<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a;
+    /// Returns all keys below the specified section, see
+    /// [`SectionIndex::walk_tree`].
+    #[must_use]
+    fn range_walk_tree<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a;
+    /// Returns the keys directly in the specified section, see
+    /// [`SectionIndex::walk_leaves`].
+    #[must_use]
+    fn range_walk_leaves<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a;
+    /// Returns the names of all subsections of the specified section, see
+    /// [`SectionIndex::section_tree`].
+    #[must_use]
+    fn range_section_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<&str>;
+    /// Returns the names of the direct subsections of the specified section,
+    /// see [`SectionIndex::section_leaves`].
+    #[must_use]
+    fn range_section_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<&str>;
+}
+
+/// Returns the bounds of the keys in a section. All keys in the section
+/// start with the section name and a dot, the next character after the dot
+/// is a slash.
+fn section_bounds(section: &KeyPath<'_>) -> (Bound<String>, Bound<String>) {
+    if section.is_root() {
+        (Bound::Unbounded, Bound::Unbounded)
+    } else {
+        (
+            Bound::Included(format!("{section}.")),
+            Bound::Excluded(format!("{section}/")),
+        )
+    }
+}
+
+fn range<'a, K, V>(
+    map: &'a BTreeMap<K, V>,
+    lower: &Bound<String>,
+    upper: &Bound<String>,
+) -> impl Iterator<Item = (&'a str, &'a V)>
+where
+    K: Borrow<str> + Ord,
+{
+    map.range::<str, _>((
+        lower.as_ref().map(String::as_str),
+        upper.as_ref().map(String::as_str),
+    ))
+    .map(|(k, v)| (k.borrow(), v))
+}
+
+/// Visits the direct children of a section. Keys in a subsection are only
+/// visited once for each subsection, with the length of the subsection name.
+fn direct_children<'a, K, V>(
+    map: &'a BTreeMap<K, V>,
+    section: &KeyPath<'_>,
+    mut f: impl FnMut(&'a str, &'a V, Option<usize>),
+) where
+    K: Borrow<str> + Ord,
+{
+    let offset = offset(section);
+    let (mut lower, upper) = section_bounds(section);
+    while let Some((key, value)) = range(map, &lower, &upper).next() {
+        if let Some(pos) = key[offset..].find('.') {
+            let end = offset + pos;
+            f(key, value, Some(end));
+            // skip the rest of the subsection
+            lower = Bound::Included(format!("{}/", &key[..end]));
+        } else {
+            f(key, value, None);
+            lower = Bound::Excluded(key.to_string());
+        }
+    }
+}
+
+impl<K, V> CniRangeExt<V> for BTreeMap<K, V>
+where
+    K: Borrow<str> + Ord,
+{
+    fn range_sub_tree<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a,
+    {
+        let section = section.into();
+        let offset = offset(&section);
+        let (lower, upper) = section_bounds(&section);
+        range(self, &lower, &upper)
+            .map(|(k, v)| (&k[offset..], v))
+            .collect()
+    }
+
+    fn range_sub_leaves<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a,
+    {
+        let section = section.into();
+        let offset = offset(&section);
+        let mut result = Vec::new();
+        direct_children(self, &section, |k, v, subsection| {
+            if subsection.is_none() {
+                result.push((&k[offset..], v));
+            }
+        });
+        result.into_iter().collect()
+    }
+
+    fn range_walk_tree<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a,
+    {
+        let section = section.into();
+        let (lower, upper) = section_bounds(&section);
+        range(self, &lower, &upper).collect()
+    }
+
+    fn range_walk_leaves<'a, 's, B>(&'a self, section: impl Into<KeyPath<'s>>) -> B
+    where
+        B: FromIterator<(&'a str, &'a V)>,
+        V: 'a,
+    {
+        let section = section.into();
+        let mut result = Vec::new();
+        direct_children(self, &section, |k, v, subsection| {
+            if subsection.is_none() {
+                result.push((k, v));
+            }
+        });
+        result.into_iter().collect()
+    }
+
+    fn range_section_tree<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<&str> {
+        let section = section.into();
+        let offset = offset(&section);
+        let (lower, upper) = section_bounds(&section);
+        let mut result = BTreeSet::new();
+        for (key, _) in range(self, &lower, &upper) {
+            let key = &key[offset..];
+            result.extend(key.match_indices('.').map(|(pos, _)| &key[..pos]));
+        }
+        result
+    }
+
+    fn range_section_leaves<'s>(&self, section: impl Into<KeyPath<'s>>) -> BTreeSet<&str> {
+        let section = section.into();
+        let offset = offset(&section);
+        let mut result = BTreeSet::new();
+        direct_children(self, &section, |k, _, subsection| {
+            if let Some(end) = subsection {
+                result.insert(&k[offset..end]);
+            }
+        });
+        result
+    }
+}
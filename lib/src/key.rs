@@ -0,0 +1,232 @@
+//! This module supplies a type for dotted keys and section names.
+
+use crate::error::KeyError;
+use crate::Opts;
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+
+/// A key or section name with its parts separated by dots, like
+/// `server.tls.cert`. The empty path is the top level section.
+///
+/// Functions that take a section name or key accept anything that can be
+/// converted into a `KeyPath`, like `&str` and `String`, so the conversion
+/// is usually not visible.
+///
+/// # Examples
+/// ```
+/// use cni_format::KeyPath;
+///
+/// let key = KeyPath::from("server.tls.cert");
+///
+/// assert_eq!(key.segments().collect::<Vec<_>>(), vec!["server", "tls", "cert"]);
+/// assert_eq!(key.parent(), Some(KeyPath::from("server.tls")));
+/// assert_eq!(key.name(), Some("cert"));
+/// assert_eq!(key.strip_prefix("server"), Some("tls.cert"));
+/// assert_eq!(key.strip_prefix("serv"), None);
+///
+/// assert_eq!(KeyPath::root().join("server").join("port").as_str(), "server.port");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyPath<'a>(Cow<'a, str>);
+
+impl<'a> KeyPath<'a> {
+    /// Creates a path from a dotted key.
+    pub fn new<S: Into<Cow<'a, str>>>(key: S) -> Self {
+        Self(key.into())
+    }
+
+    /// Returns the empty path of the top level section.
+    #[must_use]
+    pub const fn root() -> Self {
+        Self(Cow::Borrowed(""))
+    }
+
+    /// Returns the dotted key.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if this is the top level section.
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the parts of the key. The top level section
+    /// has no parts.
+    #[must_use = "iterators are lazy and do nothing unless consumed"]
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &str> {
+        // splitting the empty string would return one empty part
+        let key = if self.is_root() { None } else { Some(&*self.0) };
+        key.into_iter().flat_map(|key| key.split('.'))
+    }
+
+    /// Returns the number of parts of the key.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        if self.is_root() {
+            0
+        } else {
+            self.0.matches('.').count() + 1
+        }
+    }
+
+    /// Returns the last part of the key, or `None` for the top level
+    /// section.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.segments().next_back()
+    }
+
+    /// Returns the first part of the key and the rest of the key, or `None`
+    /// for the top level section.
+    #[must_use]
+    pub fn split_first(&self) -> Option<(&str, KeyPath<'_>)> {
+        if self.is_root() {
+            return None;
+        }
+        Some(match self.0.split_once('.') {
+            Some((first, rest)) => (first, KeyPath::from(rest)),
+            None => (&self.0, KeyPath::root()),
+        })
+    }
+
+    /// Returns the section this key is in, or `None` for the top level
+    /// section.
+    #[must_use]
+    pub fn parent(&self) -> Option<KeyPath<'_>> {
+        if self.is_root() {
+            return None;
+        }
+        Some(match self.0.rfind('.') {
+            Some(pos) => KeyPath::from(&self.0[..pos]),
+            None => KeyPath::root(),
+        })
+    }
+
+    /// Returns the key relative to this section.
+    #[must_use]
+    pub fn join<'b, K: Into<KeyPath<'b>>>(&self, key: K) -> KeyPath<'static> {
+        let key = key.into();
+        if self.is_root() {
+            key.into_owned()
+        } else if key.is_root() {
+            self.clone().into_owned()
+        } else {
+            KeyPath(Cow::Owned(format!("{self}.{key}")))
+        }
+    }
+
+    /// Returns `true` if the key is the section or in the section.
+    #[must_use]
+    pub fn starts_with<'b, K: Into<KeyPath<'b>>>(&self, section: K) -> bool {
+        let section = section.into();
+        section.is_root()
+            || self
+                .0
+                .strip_prefix(section.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    }
+
+    /// Returns the key relative to a section, without the section name and
+    /// the delimiter. Returns `None` if the key is not in the section, which
+    /// includes the key of the section itself.
+    #[must_use]
+    pub fn strip_prefix<'b, K: Into<KeyPath<'b>>>(&self, section: K) -> Option<&str> {
+        let section = section.into();
+        if section.is_root() {
+            return if self.is_root() { None } else { Some(&self.0) };
+        }
+        self.0
+            .strip_prefix(section.as_str())?
+            .strip_prefix('.')
+            .filter(|rest| !rest.is_empty())
+    }
+
+    /// Checks that the key can be parsed with the options, i.e. it is not
+    /// empty, does not start or end with a dot and only contains allowed
+    /// characters. Like the parser, this allows empty parts as in `a..b`.
+    ///
+    /// # Errors
+    /// Returns an `Err` with the key if it is not valid.
+    pub fn validate(&self, opts: Opts) -> Result<(), KeyError> {
+        if !self.is_root()
+            && !self.0.starts_with('.')
+            && !self.0.ends_with('.')
+            && self.0.chars().all(|c| crate::is_key(c, opts))
+        {
+            Ok(())
+        } else {
+            Err(KeyError {
+                key: self.0.to_string(),
+            })
+        }
+    }
+
+    /// Converts the path so it does not borrow anything.
+    #[must_use]
+    pub fn into_owned(self) -> KeyPath<'static> {
+        KeyPath(Cow::Owned(self.0.into_owned()))
+    }
+
+    /// Returns the dotted key as a string.
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+impl fmt::Display for KeyPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for KeyPath<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for KeyPath<'_> {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a str> for KeyPath<'a> {
+    fn from(key: &'a str) -> Self {
+        Self(Cow::Borrowed(key))
+    }
+}
+
+impl<'a> From<&'a String> for KeyPath<'a> {
+    fn from(key: &'a String) -> Self {
+        Self(Cow::Borrowed(key))
+    }
+}
+
+impl From<String> for KeyPath<'static> {
+    fn from(key: String) -> Self {
+        Self(Cow::Owned(key))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for KeyPath<'a> {
+    fn from(key: Cow<'a, str>) -> Self {
+        Self(key)
+    }
+}
+
+impl<'a> From<&'a KeyPath<'_>> for KeyPath<'a> {
+    fn from(key: &'a KeyPath<'_>) -> Self {
+        Self(Cow::Borrowed(&key.0))
+    }
+}
+
+impl From<KeyPath<'_>> for String {
+    fn from(key: KeyPath<'_>) -> Self {
+        key.into_string()
+    }
+}
@@ -85,17 +85,62 @@ mod api;
 #[cfg(any(feature = "api", test, doctest, doc))]
 pub use api::{CniExt, SectionFilter};
 
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod diff;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use diff::{diff, diff_by, Change, ChangeKind};
+
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod glob;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use glob::{Glob, GlobFilter, GlobMatch};
+
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod index;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use index::{CniRangeExt, SectionIndex, SectionIter, SectionView};
+
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod mutate;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use mutate::CniExtMut;
+
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod env;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use env::{overlay_env, Case, EnvOpts};
+
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub mod convert;
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod typed;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use typed::CniTypedExt;
+
+#[cfg(any(feature = "api", test, doctest, doc))]
+mod value;
+#[cfg(any(feature = "api", test, doctest, doc))]
+pub use value::{Table, Value};
+#[cfg(feature = "serde")]
+mod serde_impl;
+
 #[cfg(any(feature = "serializer", test, doctest, doc))]
 mod serializer;
 #[cfg(any(feature = "serializer", test, doctest, doc))]
-pub use serializer::to_str;
+pub use serializer::{
+    to_str, to_str_opts, to_str_with, to_writer, to_writer_with, CniWriter, Headers,
+    SerializerOptions, Sort,
+};
 
 /// Module that contains error types.
 pub mod error;
 
+mod key;
+pub use key::KeyPath;
+
 /// A struct to pass parsing options. Contains the switches to enable
 /// the different extensions.
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Opts {
     /// Whether the ini compatibility is used. Default: false
     ///
@@ -0,0 +1,153 @@
+//! This module supplies operations that change CNI data section by section.
+
+use crate::KeyPath;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+/// Provides operations on whole sections of a key/value map, the mutable
+/// counterpart of [`CniExt`](crate::CniExt).
+///
+/// Like for [`CniExt::sub_tree`](crate::CniExt::sub_tree), the keys in a
+/// section are the keys below it. The key that has the same name as the
+/// section is not part of the section.
+///
+/// This is implemented for `HashMap` and `BTreeMap` with `String` keys. You
+/// can use the implementations by importing this trait.
+///
+/// # Examples
+/// ```
+/// use cni_format::CniExtMut;
+///
+/// let mut config = cni_format::from_str("[server]\nhost = localhost\nport = 80\n")
+///     .expect("could not parse CNI");
+///
+/// config.rename_section("server", "http.server");
+/// let tls = cni_format::from_str("cert = server.pem\n").expect("could not parse CNI");
+/// config.insert_section("http.server.tls", tls);
+///
+/// let mut keys = config.keys().cloned().collect::<Vec<_>>();
+/// keys.sort();
+/// assert_eq!(keys, vec!["http.server.host", "http.server.port", "http.server.tls.cert"]);
+/// ```
+pub trait CniExtMut<V>: Sized {
+    /// Inserts the entries into the specified section, the inverse of
+    /// [`CniExt::sub_tree`](crate::CniExt::sub_tree). The section name and
+    /// a dot are prepended to the keys, existing values are replaced.
+    fn insert_section<'s, I, K>(&mut self, section: impl Into<KeyPath<'s>>, entries: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>;
+
+    /// Removes all keys in the specified section and its subsections.
+    /// Returns the removed entries with the section name and delimiter
+    /// removed from the keys, like [`CniExt::sub_tree`](crate::CniExt::sub_tree).
+    #[must_use = "use `let _ =` to discard the removed entries"]
+    fn remove_section<'s>(&mut self, section: impl Into<KeyPath<'s>>) -> Self;
+
+    /// Moves all keys in the specified section and its subsections to
+    /// another section, replacing values that already exist there. Returns
+    /// the number of moved keys.
+    fn rename_section<'a, 'b>(
+        &mut self,
+        from: impl Into<KeyPath<'a>>,
+        to: impl Into<KeyPath<'b>>,
+    ) -> usize;
+
+    /// Inserts all entries of `other`. If a key exists in both, `conflict`
+    /// is called with the key, the existing value and the new value and
+    /// returns the value to keep.
+    ///
+    /// # Examples
+    /// ```
+    /// use cni_format::CniExtMut;
+    ///
+    /// let mut base = cni_format::from_str("a = 1\nb = 2\n").expect("could not parse CNI");
+    /// let other = cni_format::from_str("b = 3\nc = 4\n").expect("could not parse CNI");
+    ///
+    /// let mut conflicts = Vec::new();
+    /// base.merge_with(other, |key, ours, theirs| {
+    ///     conflicts.push(key.to_string());
+    ///     ours + "," + &theirs
+    /// });
+    ///
+    /// assert_eq!(conflicts, vec!["b"]);
+    /// assert_eq!(base["b"], "2,3");
+    /// assert_eq!(base["c"], "4");
+    /// ```
+    fn merge_with<I, F>(&mut self, other: I, conflict: F)
+    where
+        I: IntoIterator<Item = (String, V)>,
+        F: FnMut(&str, V, V) -> V;
+}
+
+macro_rules! impl_cni_ext_mut {
+    ($map:ty, $($generics:tt)*) => {
+        impl<V, $($generics)*> CniExtMut<V> for $map {
+            fn insert_section<'s, I, K>(&mut self, section: impl Into<KeyPath<'s>>, entries: I)
+            where
+                I: IntoIterator<Item = (K, V)>,
+                K: AsRef<str>,
+            {
+                let section = section.into();
+                for (key, value) in entries {
+                    self.insert(section.join(key.as_ref()).into_string(), value);
+                }
+            }
+
+            fn remove_section<'s>(&mut self, section: impl Into<KeyPath<'s>>) -> Self {
+                let section = section.into();
+                let keys = self
+                    .keys()
+                    .filter(|key| KeyPath::from(*key).strip_prefix(&section).is_some())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let mut removed = Self::default();
+                for key in keys {
+                    if let Some(value) = self.remove(&key) {
+                        let relative = KeyPath::from(&key)
+                            .strip_prefix(&section)
+                            .unwrap_or_default()
+                            .to_string();
+                        removed.insert(relative, value);
+                    }
+                }
+                removed
+            }
+
+            fn rename_section<'a, 'b>(
+                &mut self,
+                from: impl Into<KeyPath<'a>>,
+                to: impl Into<KeyPath<'b>>,
+            ) -> usize {
+                let (from, to) = (from.into(), to.into());
+                if from == to {
+                    return self
+                        .keys()
+                        .filter(|key| KeyPath::from(*key).strip_prefix(&from).is_some())
+                        .count();
+                }
+                let moved = self.remove_section(from);
+                let len = moved.len();
+                self.insert_section(to, moved);
+                len
+            }
+
+            fn merge_with<I, F>(&mut self, other: I, mut conflict: F)
+            where
+                I: IntoIterator<Item = (String, V)>,
+                F: FnMut(&str, V, V) -> V,
+            {
+                for (key, theirs) in other {
+                    let value = match self.remove(&key) {
+                        Some(ours) => conflict(&key, ours, theirs),
+                        None => theirs,
+                    };
+                    self.insert(key, value);
+                }
+            }
+        }
+    };
+}
+
+impl_cni_ext_mut!(HashMap<String, V, S>, S: BuildHasher + Default);
+impl_cni_ext_mut!(BTreeMap<String, V>,);
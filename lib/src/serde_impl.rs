@@ -0,0 +1,167 @@
+//! This module implements `Serialize` and `Deserialize` for [`Value`] and
+//! [`Table`] if the `serde` feature is enabled.
+//!
+//! A table is a map. If it has a value itself, that value is stored with the
+//! empty string as key, since that is not a valid CNI key. Values of other
+//! types, like numbers and booleans, are converted to strings when they are
+//! deserialized, sequences become tables with the indices as keys.
+//!
+//! A value is deserialized as a newtype struct with a special name, so that
+//! `serde_cni` can present CNI values as they are written instead of
+//! inferring their type, which would turn e.g. `1.10` into `1.1`.
+
+use crate::value::{Table, Value};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt;
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Table(table) => table.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Table {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map =
+            serializer.serialize_map(Some(self.len() + usize::from(self.value().is_some())))?;
+        if let Some(value) = self.value() {
+            map.serialize_entry("", value)?;
+        }
+        for (key, value) in self {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// The name of the newtype struct a [`Value`] is deserialized as. `serde_cni`
+/// checks for this name.
+const VALUE_NAME: &str = "$cni_format::Value";
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a CNI value or section")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut table = Table::new();
+        let mut i = 0_usize;
+        while let Some(value) = seq.next_element()? {
+            table.insert_value(i.to_string(), value);
+            i += 1;
+        }
+        Ok(Value::Table(table))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut table = Table::new();
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            if key.is_empty() {
+                match value {
+                    Value::String(value) => {
+                        table.set_value(Some(value));
+                    }
+                    Value::Table(_) => {
+                        return Err(de::Error::invalid_type(
+                            Unexpected::Map,
+                            &"a string for the value of the section",
+                        ))
+                    }
+                }
+            } else {
+                table.insert_value(key, value);
+            }
+        }
+        Ok(Value::Table(table))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(VALUE_NAME, ValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Table {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match deserializer.deserialize_map(ValueVisitor)? {
+            Value::Table(table) => Ok(table),
+            Value::String(value) => Err(de::Error::invalid_type(
+                Unexpected::Str(&value),
+                &"a CNI section",
+            )),
+        }
+    }
+}
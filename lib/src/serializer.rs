@@ -1,14 +1,213 @@
+use crate::error::KeyError;
+use crate::{KeyPath, Opts};
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
 
-fn format_value(value: String) -> String {
+fn format_value(value: &str, opts: Opts) -> Cow<'_, str> {
     if value.is_empty() {
-        "#empty".to_string()
-    } else if value.contains(|c| c == '`' || crate::is_vertical_ws(c) || c == '#' || c == ';') {
+        Cow::Borrowed("#empty")
+    } else if value.trim() != value
+        || value.contains(|c| c == '`' || crate::is_vertical_ws(c) || crate::is_comment(c, opts))
+    {
         // This has to be stored as a raw value.
-        format!("`{}`", value.replace("`", "``"))
+        Cow::Owned(format!("`{}`", value.replace('`', "``")))
     } else {
         // normal value
-        value
+        Cow::Borrowed(value)
+    }
+}
+
+/// When section headers are used by the serializer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Headers {
+    /// No section headers are used, all keys are written in full.
+    Never,
+    /// Every key that contains a dot is written in a section.
+    Always,
+    /// A section header is only used if the section contains at least this
+    /// many keys. Deeper sections are considered first, so a key is written
+    /// in the deepest section that is large enough.
+    Threshold(usize),
+}
+
+/// The order in which keys are written by the serializer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sort {
+    /// Keys without a section come first, then the sections sorted by name.
+    /// This uses the fewest section headers.
+    Sections,
+    /// Keys are sorted by name. A section header may appear more than once.
+    Keys,
+    /// Keys are written in the order they are given. A section header may
+    /// appear more than once.
+    Unsorted,
+}
+
+/// A struct to pass serialization options.
+///
+/// The default options use a section header for the first part of every key
+/// that contains a dot.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "serializer")] {
+/// use cni_format::{Headers, SerializerOptions};
+///
+/// let opts = SerializerOptions {
+///     headers: Headers::Threshold(2),
+///     max_depth: usize::MAX,
+///     blank_line: true,
+///     ..SerializerOptions::default()
+/// };
+///
+/// assert_eq!(
+///     cni_format::to_str_with(vec![("a.b.c", "1"), ("a.b.d", "2"), ("a.e", "3")], &opts).unwrap(),
+///     "a.e = 3\n\n[a.b]\nc = 1\nd = 2\n"
+/// );
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializerOptions {
+    /// When section headers are used. Default: [`Headers::Always`]
+    pub headers: Headers,
+    /// How many parts of a key a section header may contain at most, e.g.
+    /// with 1 the key `a.b.c` would be written as `b.c` in the section `a`.
+    /// Default: 1
+    pub max_depth: usize,
+    /// Whether an empty line is written before each section header.
+    /// Default: false
+    pub blank_line: bool,
+    /// Whether there are spaces around the equals sign. Default: true
+    pub spacing: bool,
+    /// The order in which keys are written. Default: [`Sort::Sections`]
+    pub sort: Sort,
+    /// Comments that are written before the key or section header with the
+    /// same name. Comments for sections use the full name of the section.
+    /// Default: no comments
+    pub comments: BTreeMap<String, String>,
+    /// The CNI options of the output. If `ini` is enabled, comments start
+    /// with `;` instead of `#`. Default: [`Opts::default`]
+    pub cni: Opts,
+}
+
+impl Default for SerializerOptions {
+    fn default() -> Self {
+        Self {
+            headers: Headers::Always,
+            max_depth: 1,
+            blank_line: false,
+            spacing: true,
+            sort: Sort::Sections,
+            comments: BTreeMap::new(),
+            cni: Opts::default(),
+        }
+    }
+}
+
+/// Converts a [`KeyError`] so it can be returned from writing functions.
+fn invalid_input(err: KeyError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err)
+}
+
+/// Returns the length of the section name that contains at most `depth`
+/// parts of the key, but never the whole key.
+///
+/// Keys with empty parts like `a..b` are not split next to an empty part,
+/// since neither the section name nor the key may start or end with a dot.
+fn prefix_len(key: &str, depth: usize) -> usize {
+    let path = KeyPath::from(key);
+    let parts = path.depth().saturating_sub(1).min(depth);
+    (0..=parts)
+        .rev()
+        .map(|parts| {
+            // the length of the parts and the dots between them
+            path.segments()
+                .take(parts)
+                .map(|part| part.len() + 1)
+                .sum::<usize>()
+                .saturating_sub(1)
+        })
+        .find(|&len| len == 0 || !key[..len].ends_with('.') && !key[len + 1..].starts_with('.'))
+        .unwrap_or(0)
+}
+
+/// Compares two keys part by part, keys without dots first.
+fn cmp_keys(a: &str, b: &str) -> Ordering {
+    let (a, b) = (KeyPath::from(a), KeyPath::from(b));
+    (a.depth() > 1)
+        .cmp(&(b.depth() > 1))
+        .then_with(|| a.segments().cmp(b.segments()))
+}
+
+/// Decides which section each key is written in, returned as the length of
+/// the section name.
+fn assign_sections<K: AsRef<str>>(keys: &[K], opts: &SerializerOptions) -> Vec<usize> {
+    match opts.headers {
+        Headers::Never => vec![0; keys.len()],
+        Headers::Always => keys
+            .iter()
+            .map(|key| prefix_len(key.as_ref(), opts.max_depth))
+            .collect(),
+        Headers::Threshold(threshold) => {
+            // the possible section names of each key, as their lengths
+            let prefixes = keys
+                .iter()
+                .map(|key| {
+                    let key = key.as_ref();
+                    let mut prefixes = (1..KeyPath::from(key).depth())
+                        .take(opts.max_depth)
+                        .map(|depth| prefix_len(key, depth))
+                        .filter(|&len| len > 0)
+                        .collect::<Vec<_>>();
+                    prefixes.dedup();
+                    prefixes
+                })
+                .collect::<Vec<_>>();
+
+            // the keys below each possible section name and how many of
+            // them are not in a deeper section yet
+            let mut candidates = BTreeMap::<&str, (Vec<usize>, usize)>::new();
+            for (i, (key, prefixes)) in keys.iter().zip(&prefixes).enumerate() {
+                for &len in prefixes {
+                    let (members, count) = candidates.entry(&key.as_ref()[..len]).or_default();
+                    members.push(i);
+                    *count += 1;
+                }
+            }
+
+            // deepest first
+            let mut order = candidates.keys().copied().collect::<Vec<_>>();
+            order.sort_by_cached_key(|candidate| {
+                std::cmp::Reverse(KeyPath::from(*candidate).depth())
+            });
+
+            let mut sections = vec![0; keys.len()];
+            for candidate in order {
+                let (members, count) = candidates
+                    .get_mut(candidate)
+                    .map(std::mem::take)
+                    .unwrap_or_default();
+                if count < threshold {
+                    continue;
+                }
+                for i in members {
+                    if sections[i] != 0 {
+                        continue;
+                    }
+                    sections[i] = candidate.len();
+                    // the key no longer counts for the sections above
+                    let key = keys[i].as_ref();
+                    for &len in prefixes[i].iter().filter(|&&len| len < candidate.len()) {
+                        if let Some((_, count)) = candidates.get_mut(&key[..len]) {
+                            *count -= 1;
+                        }
+                    }
+                }
+            }
+            sections
+        }
     }
 }
 
@@ -16,7 +215,7 @@ fn format_value(value: String) -> String {
 /// values and map types.
 /// The output will contain as few section headers as possible, but if a key
 /// consists of multiple parts separated by a dot, the first one will always be
-/// used for the section name
+/// used for the section name. Use [`to_str_with`] to change this.
 ///
 /// ```
 /// # #[cfg(feature = "serializer")] {
@@ -24,63 +223,352 @@ fn format_value(value: String) -> String {
 /// map.insert("a", "b");
 ///
 /// assert_eq!(
-///     &cni_format::to_str(map),
+///     cni_format::to_str(map).unwrap(),
 ///     "a = b\n"
 /// );
 ///
 /// assert_eq!(
-///     &cni_format::to_str(vec![
+///     cni_format::to_str(vec![
 ///         ("a.b", "c"),
-///     ]),
+///     ]).unwrap(),
 ///     "[a]\nb = c\n"
 /// );
 /// # }
 /// ```
-pub fn to_str<I, K, V>(data: I) -> String
+///
+/// # Errors
+/// Returns an `Err` if a key is not valid with the default options, see
+/// [`to_str_opts`].
+pub fn to_str<I, K, V>(data: I) -> Result<String, KeyError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: ToString,
+{
+    to_str_with(data, &SerializerOptions::default())
+}
+
+/// Turn a key/value store into CNI format text that can be parsed with the
+/// specified parsing options. Parsing the result with
+/// [`from_str_opts`](crate::from_str_opts) and the same options returns
+/// the same data.
+///
+/// Values are written as raw values if they could not be parsed back
+/// otherwise, e.g. because of leading whitespace or comment characters.
+///
+/// # Errors
+/// Returns an `Err` if a key is empty, starts or ends with a dot, contains
+/// two dots in a row or contains characters that are not allowed with the
+/// options.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "serializer")] {
+/// use cni_format::Opts;
+///
+/// let ini = Opts { ini: true, more_keys: false };
+///
+/// assert_eq!(cni_format::to_str_opts(vec![("a", "b;c")], Opts::default()).unwrap(), "a = b;c\n");
+/// assert_eq!(cni_format::to_str_opts(vec![("a", "b;c")], ini).unwrap(), "a = `b;c`\n");
+/// assert!(cni_format::to_str_opts(vec![("a b", "c")], ini).is_err());
+/// # }
+/// ```
+pub fn to_str_opts<I, K, V>(data: I, opts: Opts) -> Result<String, KeyError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: ToString,
+{
+    to_str_with(
+        data,
+        &SerializerOptions {
+            cni: opts,
+            ..SerializerOptions::default()
+        },
+    )
+}
+
+/// Turn a key/value store into CNI format text using the specified options,
+/// see [`SerializerOptions`].
+///
+/// # Errors
+/// Returns an `Err` if a key is not valid, see [`to_str_opts`].
+pub fn to_str_with<I, K, V>(data: I, opts: &SerializerOptions) -> Result<String, KeyError>
 where
     I: IntoIterator<Item = (K, V)>,
     K: AsRef<str>,
     V: ToString,
 {
-    let mut data = data
-        .into_iter()
-        .map(|(k, v)| (k, v.to_string()))
+    let (keys, values) = collect(data, opts)?;
+    let mut buf = Vec::new();
+    // writing to a Vec can not fail
+    let _ = write_data(&mut buf, &keys, values, opts);
+    // only strings are written so this must be valid UTF-8
+    Ok(String::from_utf8(buf).unwrap_or_default())
+}
+
+/// Writes a key/value store as CNI format text, see [`to_str`].
+///
+/// # Errors
+/// Returns an `Err` if writing fails or with the kind
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) containing a [`KeyError`]
+/// if a key is not valid. In that case nothing is written.
+pub fn to_writer<W, I, K, V>(writer: W, data: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: ToString,
+{
+    to_writer_with(writer, data, &SerializerOptions::default())
+}
+
+/// Writes a key/value store as CNI format text using the specified options,
+/// see [`to_str_with`].
+///
+/// The data is sorted and divided into sections before anything is written.
+/// Use [`CniWriter`] to write data in the order it is produced instead.
+///
+/// # Errors
+/// Returns an `Err` for the same reasons as [`to_writer`].
+pub fn to_writer_with<W, I, K, V>(writer: W, data: I, opts: &SerializerOptions) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: ToString,
+{
+    let (keys, values) = collect(data, opts).map_err(invalid_input)?;
+    write_data(writer, &keys, values, opts)
+}
+
+/// Collects the data and checks the keys before anything is written.
+#[allow(clippy::type_complexity)]
+fn collect<I, K, V>(data: I, opts: &SerializerOptions) -> Result<(Vec<K>, Vec<String>), KeyError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: ToString,
+{
+    let (keys, values): (Vec<K>, Vec<String>) =
+        data.into_iter().map(|(k, v)| (k, v.to_string())).unzip();
+    for key in &keys {
+        KeyPath::from(key.as_ref()).validate(opts.cni)?;
+    }
+    Ok((keys, values))
+}
+
+fn write_data<W, K>(
+    writer: W,
+    keys: &[K],
+    values: Vec<String>,
+    opts: &SerializerOptions,
+) -> io::Result<()>
+where
+    W: Write,
+    K: AsRef<str>,
+{
+    let sections = assign_sections(keys, opts);
+
+    let mut data = keys
+        .iter()
+        .map(AsRef::as_ref)
+        .zip(sections)
+        .zip(values)
         .collect::<Vec<_>>();
 
-    // use special ordering to ensure fewest number of section headings:
-    // - sort by keys
-    // - keys without dots first
-    // - then sort alphabetically grouped in (sub)sections
-    data.sort_unstable_by(|(a, _), (b, _)| {
-        a.as_ref()
-            .contains('.')
-            .cmp(&b.as_ref().contains('.'))
-            .then(
-                a.as_ref()
-                    .split('.')
-                    .zip(b.as_ref().split('.'))
-                    .fold(Ordering::Equal, |acc, (a, b)| acc.then(a.cmp(b))),
-            )
-    });
-
-    let mut section = String::new();
-    let mut buf = String::new();
-
-    for (key, value) in data {
-        let key = key.as_ref();
-
-        let key = if let Some(pos) = key.find('.') {
-            let (new_section, key) = key.split_at(pos);
-            if section != new_section {
-                buf.push_str(&format!("[{}]\n", new_section));
-                section = new_section.to_string();
-            }
-            &key[1..] // remove dot
+    match opts.sort {
+        Sort::Sections => data.sort_by(|((a, a_len), _), ((b, b_len), _)| {
+            // keys without a section first, then grouped by section
+            (*a_len > 0)
+                .cmp(&(*b_len > 0))
+                .then_with(|| {
+                    KeyPath::from(&a[..*a_len])
+                        .segments()
+                        .cmp(KeyPath::from(&b[..*b_len]).segments())
+                })
+                .then_with(|| cmp_keys(a, b))
+        }),
+        Sort::Keys => data.sort_by_key(|((key, _), _)| *key),
+        Sort::Unsorted => (),
+    }
+
+    let mut writer = CniWriter::with_options(writer, opts.clone());
+
+    for ((key, len), value) in data {
+        let section = &key[..len];
+        if writer.section() != section {
+            writer.begin_section(section)?;
+        }
+
+        writer.write_pair(
+            KeyPath::from(key).strip_prefix(section).unwrap_or(key),
+            &value,
+        )?;
+    }
+
+    writer.flush()
+}
+
+/// Writes each line of the comment with a comment marker.
+fn write_comment_lines<W: Write>(writer: &mut W, opts: Opts, comment: &str) -> io::Result<()> {
+    let marker: &[u8] = if opts.ini { b";" } else { b"#" };
+    let comment = comment.replace("\r\n", "\n");
+    let comment = comment
+        .strip_suffix(crate::is_vertical_ws)
+        .unwrap_or(&comment);
+    for line in comment.split(crate::is_vertical_ws) {
+        writer.write_all(marker)?;
+        if !line.is_empty() {
+            writer.write_all(b" ")?;
+            writer.write_all(line.as_bytes())?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Writes CNI format text incrementally. The caller controls the order of
+/// sections and keys, so a section header may appear more than once.
+///
+/// Values are written as raw values if necessary. Of the
+/// [`SerializerOptions`], `headers`, `max_depth` and `sort` are not used.
+/// Comments from the options are written automatically before the
+/// respective section header or key.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "serializer")] {
+/// use cni_format::CniWriter;
+///
+/// let mut writer = CniWriter::new(Vec::new());
+/// writer.write_comment("generated, do not edit").unwrap();
+/// writer.write_pair("name", "example").unwrap();
+/// writer.begin_section("server").unwrap();
+/// writer.write_pair("motd", "line 1\nline 2").unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(writer.into_inner()).unwrap(),
+///     "# generated, do not edit\nname = example\n[server]\nmotd = `line 1\nline 2`\n"
+/// );
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CniWriter<W> {
+    writer: W,
+    opts: SerializerOptions,
+    /// The current section, initially the top level.
+    section: String,
+    /// Whether anything has been written yet.
+    started: bool,
+}
+
+impl<W: Write> CniWriter<W> {
+    /// Creates a writer with the default options.
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, SerializerOptions::default())
+    }
+
+    /// Creates a writer with the specified options.
+    pub fn with_options(writer: W, opts: SerializerOptions) -> Self {
+        Self {
+            writer,
+            opts,
+            section: String::new(),
+            started: false,
+        }
+    }
+
+    /// Returns the name of the current section, which is empty for the top
+    /// level.
+    pub fn section(&self) -> &str {
+        &self.section
+    }
+
+    /// Writes a section header, so the following keys are in this section.
+    /// An empty name returns to the top level.
+    ///
+    /// # Errors
+    /// Returns an `Err` if writing fails or with the kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) containing a
+    /// [`KeyError`] if the name is not valid. In that case nothing is
+    /// written.
+    pub fn begin_section(&mut self, section: &str) -> io::Result<()> {
+        if !section.is_empty() {
+            KeyPath::from(section)
+                .validate(self.opts.cni)
+                .map_err(invalid_input)?;
+        }
+        if self.opts.blank_line && self.started {
+            self.writer.write_all(b"\n")?;
+        }
+        self.write_comment_for(section)?;
+        self.writer.write_all(b"[")?;
+        self.writer.write_all(section.as_bytes())?;
+        self.writer.write_all(b"]\n")?;
+        self.section = section.to_string();
+        self.started = true;
+        Ok(())
+    }
+
+    /// Writes a key and value in the current section. The key must not
+    /// contain the section name.
+    ///
+    /// # Errors
+    /// Returns an `Err` if writing fails or with the kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) containing a
+    /// [`KeyError`] if the key is not valid. In that case nothing is
+    /// written.
+    pub fn write_pair(&mut self, key: &str, value: &str) -> io::Result<()> {
+        KeyPath::from(key)
+            .validate(self.opts.cni)
+            .map_err(invalid_input)?;
+        if self.section.is_empty() {
+            self.write_comment_for(key)?;
         } else {
-            key
-        };
-        buf.push_str(&format!("{} = {}\n", key, format_value(value)));
+            self.write_comment_for(&format!("{}.{key}", self.section))?;
+        }
+        let separator = if self.opts.spacing { " = " } else { "=" };
+        self.writer.write_all(key.as_bytes())?;
+        self.writer.write_all(separator.as_bytes())?;
+        self.writer
+            .write_all(format_value(value, self.opts.cni).as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Writes a comment. Each line of the comment gets its own comment
+    /// marker, a line break at the end is ignored.
+    ///
+    /// # Errors
+    /// Returns an `Err` if writing fails.
+    pub fn write_comment(&mut self, comment: &str) -> io::Result<()> {
+        write_comment_lines(&mut self.writer, self.opts.cni, comment)?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Writes the comment from the options for the key or section, if there
+    /// is one.
+    fn write_comment_for(&mut self, name: &str) -> io::Result<()> {
+        if let Some(comment) = self.opts.comments.get(name) {
+            write_comment_lines(&mut self.writer, self.opts.cni, comment)?;
+            self.started = true;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    ///
+    /// # Errors
+    /// Returns an `Err` if flushing fails.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
     }
 
-    buf
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
 }
@@ -129,4 +129,13 @@ fn section_leaves() {
             .collect::<Vec<_>>(),
         vec!["b"]
     );
+
+    let mut map = HashMap::<String, String>::new();
+    map.insert("a.b.c".into(), "c".into());
+    map.insert("d.e.f".into(), "f".into());
+
+    assert_eq!(
+        map.section_leaves("").into_iter().collect::<Vec<_>>(),
+        vec!["a", "d"]
+    );
 }
@@ -0,0 +1,64 @@
+use crate::{Change, ChangeKind};
+use std::collections::HashMap;
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn diff() {
+    let old = map(&[("a", "1"), ("s.b", "2"), ("s.t.c", "3"), ("z", "4")]);
+    let new = map(&[("a", "1"), ("s.b", "5"), ("s-x.d", "6"), ("z", "4")]);
+    let (two, five, three, six) = (
+        "2".to_string(),
+        "5".to_string(),
+        "3".to_string(),
+        "6".to_string(),
+    );
+
+    assert_eq!(
+        crate::diff(&old, &new),
+        vec![
+            Change {
+                key: "s.b",
+                old: Some(&two),
+                new: Some(&five),
+            },
+            Change {
+                key: "s.t.c",
+                old: Some(&three),
+                new: None,
+            },
+            Change {
+                key: "s-x.d",
+                old: None,
+                new: Some(&six),
+            },
+        ]
+    );
+
+    assert!(crate::diff(&old, &old).is_empty());
+}
+
+#[test]
+fn change() {
+    let value = 1;
+    let change = Change {
+        key: "a.b.c",
+        old: Some(&value),
+        new: None,
+    };
+    assert_eq!(change.kind(), ChangeKind::Removed);
+    assert_eq!(change.section(), "a.b");
+
+    let change = Change {
+        key: "a",
+        old: None,
+        new: Some(&value),
+    };
+    assert_eq!(change.kind(), ChangeKind::Added);
+    assert_eq!(change.section(), "");
+}
@@ -0,0 +1,65 @@
+use crate::{Case, EnvOpts};
+use std::collections::HashMap;
+
+fn test_vars() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("APP_SERVER__PORT", "8080"),
+        ("APP_LOG_LEVEL", "debug"),
+        ("APP_", "empty"),
+        ("APP___BROKEN", "broken"),
+        ("HOME", "/root"),
+    ]
+}
+
+#[test]
+fn map_vars() {
+    let mut map = HashMap::<String, String>::new();
+    map.insert("server.port".into(), "8080".into());
+    map.insert("log_level".into(), "debug".into());
+
+    assert_eq!(
+        EnvOpts::with_prefix("APP_")
+            .map_vars(test_vars())
+            .collect::<HashMap<_, _>>(),
+        map
+    );
+}
+
+#[test]
+fn separator_and_case() {
+    let opts = EnvOpts {
+        prefix: "APP_".into(),
+        separator: "_".into(),
+        case: Case::Preserve,
+    };
+
+    // `SERVER__PORT` would have an empty part
+    let mut map = HashMap::<String, String>::new();
+    map.insert("LOG.LEVEL".into(), "debug".into());
+
+    assert_eq!(opts.map_vars(test_vars()).collect::<HashMap<_, _>>(), map);
+}
+
+#[test]
+fn empty_parts() {
+    let opts = EnvOpts {
+        prefix: String::new(),
+        separator: "_".into(),
+        case: Case::Lower,
+    };
+
+    assert_eq!(opts.key("SERVER_PORT"), Some("server.port".to_string()));
+    assert_eq!(opts.key("SERVER__PORT"), None);
+    assert_eq!(opts.key("_SERVER"), None);
+    assert_eq!(opts.key("SERVER_"), None);
+}
+
+#[test]
+fn overlay() {
+    let mut map = crate::from_str("[server]\nport = 80\nhost = localhost\n").unwrap();
+    map.extend(EnvOpts::with_prefix("APP_").map_vars(test_vars()));
+
+    assert_eq!(map["server.port"], "8080");
+    assert_eq!(map["server.host"], "localhost");
+    assert_eq!(map["log_level"], "debug");
+}
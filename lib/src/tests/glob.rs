@@ -0,0 +1,94 @@
+use crate::{CniExt, Glob};
+use std::collections::BTreeMap;
+
+fn test_map() -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    map.insert("servers.web1.host".into(), "10.0.0.1".into());
+    map.insert("servers.web1.port".into(), "80".into());
+    map.insert("servers.web2.host".into(), "10.0.0.2".into());
+    map.insert("servers.eu.db.host".into(), "10.0.1.1".into());
+    map.insert("servers.host".into(), "default".into());
+    map.insert("name".into(), "test".into());
+    map
+}
+
+/// Returns the matching keys with their captures.
+fn walk(pattern: &str) -> Vec<(String, Vec<String>)> {
+    test_map()
+        .iter()
+        .walk_glob(pattern)
+        .map(|m| {
+            let captures = m.captures().map(ToString::to_string).collect();
+            (m.key.clone(), captures)
+        })
+        .collect()
+}
+
+#[test]
+fn any() {
+    assert_eq!(
+        walk("servers.*.host"),
+        vec![
+            ("servers.web1.host".into(), vec!["web1".into()]),
+            ("servers.web2.host".into(), vec!["web2".into()]),
+        ]
+    );
+    assert_eq!(
+        walk("servers.web1.*"),
+        vec![
+            ("servers.web1.host".into(), vec!["host".into()]),
+            ("servers.web1.port".into(), vec!["port".into()]),
+        ]
+    );
+    assert_eq!(walk("*"), vec![("name".into(), vec!["name".into()])]);
+    assert!(walk("servers.*.*.*.host").is_empty());
+}
+
+#[test]
+fn any_depth() {
+    assert_eq!(
+        walk("servers.**.host"),
+        vec![
+            ("servers.eu.db.host".into(), vec!["eu.db".into()]),
+            ("servers.host".into(), vec![String::new()]),
+            ("servers.web1.host".into(), vec!["web1".into()]),
+            ("servers.web2.host".into(), vec!["web2".into()]),
+        ]
+    );
+    assert_eq!(walk("**").len(), test_map().len());
+    assert_eq!(
+        walk("**.*.port"),
+        vec![(
+            "servers.web1.port".into(),
+            vec!["servers".into(), "web1".into()]
+        )]
+    );
+}
+
+#[test]
+fn glob() {
+    let glob = Glob::new("a.**.*");
+    assert_eq!(glob.captures("a.b"), Some(vec![2..2, 2..3]));
+    assert_eq!(glob.captures("a.b.c.d"), Some(vec![2..5, 6..7]));
+    assert_eq!(glob.captures("a"), None);
+
+    // wildcards only match whole parts
+    assert!(Glob::new("a*").is_match("a*"));
+    assert!(!Glob::new("a*").is_match("ab"));
+
+    assert!(Glob::new("").is_match(""));
+    assert!(!Glob::new("").is_match("a"));
+    assert!(Glob::new("**").is_match(""));
+}
+
+#[test]
+fn glob_match() {
+    let map = test_map();
+    let m = map.iter().walk_glob("servers.*.port").next().unwrap();
+    assert_eq!(m.capture(0), Some("web1"));
+    assert_eq!(m.capture(1), None);
+    assert_eq!(
+        m.into_pair(),
+        (&"servers.web1.port".to_string(), &"80".to_string())
+    );
+}
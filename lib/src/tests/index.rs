@@ -0,0 +1,182 @@
+use crate::{CniExt, CniRangeExt, SectionIndex};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+fn test_map() -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    map.insert("a".into(), "a".into());
+    map.insert("a.b".into(), "b".into());
+    map.insert("a.c".into(), "c".into());
+    map.insert("a.b.c".into(), "c".into());
+    map.insert("a-b.c".into(), "d".into());
+    map.insert("x.y.z".into(), "z".into());
+    map
+}
+
+/// Converts the results of the index to the results of [`CniExt`].
+fn owned(pairs: Vec<(&str, &String)>) -> HashMap<String, String> {
+    pairs
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect()
+}
+
+fn owned_set(set: BTreeSet<&str>) -> BTreeSet<String> {
+    set.into_iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn index_matches_api() {
+    let map = test_map();
+    let hash_map = map.clone().into_iter().collect::<HashMap<_, _>>();
+    let index = SectionIndex::new(&map);
+
+    assert_eq!(index.len(), map.len());
+    assert_eq!(index.get("a.b"), Some(&"b".to_string()));
+    assert_eq!(index.get("x.y"), None);
+
+    for section in ["", "a", "a.b", "a-b", "x", "x.y", "missing"] {
+        assert_eq!(
+            owned(index.sub_tree(section)),
+            hash_map.sub_tree(section),
+            "{section}"
+        );
+        assert_eq!(
+            owned(index.sub_leaves(section)),
+            hash_map.sub_leaves(section),
+            "{section}"
+        );
+        assert_eq!(
+            owned(index.walk_tree(section)),
+            hash_map.clone().walk_tree(section).collect(),
+            "{section}"
+        );
+        assert_eq!(
+            owned(index.walk_leaves(section)),
+            hash_map.clone().walk_leaves(section).collect(),
+            "{section}"
+        );
+        assert_eq!(
+            owned_set(index.section_tree(section)),
+            hash_map.section_tree(section),
+            "{section}"
+        );
+        assert_eq!(
+            owned_set(index.section_leaves(section)),
+            hash_map.section_leaves(section),
+            "{section}"
+        );
+    }
+}
+
+#[test]
+fn range_matches_api() {
+    let map = test_map();
+
+    for section in ["", "a", "a.b", "a-b", "x", "x.y", "missing"] {
+        assert_eq!(
+            owned(map.range_sub_tree(section)),
+            map.sub_tree(section).into_iter().collect(),
+            "{section}"
+        );
+        assert_eq!(
+            owned(map.range_sub_leaves(section)),
+            map.sub_leaves(section).into_iter().collect(),
+            "{section}"
+        );
+        assert_eq!(
+            owned(map.range_walk_tree(section)),
+            map.clone().walk_tree(section).collect(),
+            "{section}"
+        );
+        assert_eq!(
+            owned(map.range_walk_leaves(section)),
+            map.clone().walk_leaves(section).collect(),
+            "{section}"
+        );
+        assert_eq!(
+            owned_set(map.range_section_tree(section)),
+            map.section_tree(section),
+            "{section}"
+        );
+        assert_eq!(
+            owned_set(map.range_section_leaves(section)),
+            map.section_leaves(section),
+            "{section}"
+        );
+    }
+}
+
+#[test]
+fn index_order() {
+    let map = test_map();
+    let index = SectionIndex::new(&map);
+
+    // keys of a section are next to each other, unlike in the map
+    assert_eq!(
+        index
+            .walk_tree::<Vec<_>>("")
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>(),
+        vec!["a", "a.b", "a.b.c", "a.c", "a-b.c", "x.y.z"]
+    );
+}
+
+#[test]
+fn many_sections() {
+    let map = (0..10_000)
+        .map(|i| (format!("s{}.t{}.k", i % 100, i), i.to_string()))
+        .collect::<HashMap<_, _>>();
+    let index = SectionIndex::new(&map);
+
+    assert_eq!(index.section_leaves("").len(), 100);
+    assert_eq!(index.section_tree("").len(), 10_100);
+    assert_eq!(index.sub_tree::<Vec<_>>("s7").len(), 100);
+    assert_eq!(map.section_tree("").len(), 10_100);
+}
+
+#[test]
+fn view() {
+    let map = test_map();
+    let index = SectionIndex::new(&map);
+
+    let root = index.root();
+    assert_eq!(root.name(), "");
+    assert_eq!(root.len(), map.len());
+    assert_eq!(root.get("a.b.c"), Some(&"c".to_string()));
+    assert_eq!(root.get(""), None);
+
+    let a = index.view("a").unwrap();
+    assert_eq!(a.name(), "a");
+    assert_eq!(a.len(), 3);
+    assert!(!a.is_empty());
+    // the value of the section itself is not part of the view
+    assert_eq!(a.get(""), None);
+    assert_eq!(a.get("b.c"), Some(&"c".to_string()));
+    assert_eq!(a.keys().collect::<Vec<_>>(), vec!["b", "b.c", "c"]);
+    assert_eq!(
+        owned(a.iter().collect()),
+        map.sub_tree("a").into_iter().collect()
+    );
+
+    let b = a.view("b").unwrap();
+    assert_eq!(b.name(), "a.b");
+    assert_eq!(
+        b.into_iter().collect::<Vec<_>>(),
+        vec![("c", &"c".to_string())]
+    );
+    let mut keys = Vec::new();
+    for (key, _) in &a {
+        keys.push(key);
+    }
+    assert_eq!(keys, vec!["b", "b.c", "c"]);
+    assert_eq!(
+        index.view("x.y").unwrap().keys().collect::<Vec<_>>(),
+        vec!["z"]
+    );
+
+    // sections without keys below them
+    assert!(a.view("c").is_none());
+    assert!(a.view("missing").is_none());
+    assert!(index.view("x.y.z").is_none());
+}
@@ -0,0 +1,76 @@
+use crate::error::KeyError;
+use crate::{KeyPath, Opts};
+
+#[test]
+fn segments() {
+    let key = KeyPath::from("a.b.c");
+    assert_eq!(key.segments().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    assert_eq!(key.depth(), 3);
+    assert_eq!(key.name(), Some("c"));
+    assert_eq!(key.split_first(), Some(("a", KeyPath::from("b.c"))));
+
+    let root = KeyPath::root();
+    assert!(root.is_root());
+    assert_eq!(root.segments().count(), 0);
+    assert_eq!(root.depth(), 0);
+    assert_eq!(root.name(), None);
+    assert_eq!(root.split_first(), None);
+}
+
+#[test]
+fn parent() {
+    let key = KeyPath::from("a.b");
+    assert_eq!(key.parent(), Some(KeyPath::from("a")));
+    assert_eq!(KeyPath::from("a").parent(), Some(KeyPath::root()));
+    assert_eq!(KeyPath::root().parent(), None);
+}
+
+#[test]
+fn join() {
+    assert_eq!(KeyPath::from("a").join("b.c"), KeyPath::from("a.b.c"));
+    assert_eq!(KeyPath::root().join("b"), KeyPath::from("b"));
+    assert_eq!(KeyPath::from("a").join(KeyPath::root()), KeyPath::from("a"));
+    assert_eq!(KeyPath::root().join(""), KeyPath::root());
+}
+
+#[test]
+fn strip_prefix() {
+    let key = KeyPath::from("a.b.c");
+    assert_eq!(key.strip_prefix("a"), Some("b.c"));
+    assert_eq!(key.strip_prefix("a.b"), Some("c"));
+    assert_eq!(key.strip_prefix(""), Some("a.b.c"));
+    // the key of the section itself is not in the section
+    assert_eq!(key.strip_prefix("a.b.c"), None);
+    // only whole parts are a prefix
+    assert_eq!(key.strip_prefix("a.b.c.d"), None);
+    assert_eq!(KeyPath::from("ab.c").strip_prefix("a"), None);
+    assert_eq!(KeyPath::root().strip_prefix(""), None);
+
+    assert!(key.starts_with("a.b.c"));
+    assert!(key.starts_with("a"));
+    assert!(key.starts_with(""));
+    assert!(!key.starts_with("a.b.cd"));
+}
+
+#[test]
+fn validate() {
+    let opts = Opts::default();
+    let more_keys = Opts {
+        ini: false,
+        more_keys: true,
+    };
+
+    assert_eq!(KeyPath::from("a.b-c_d").validate(opts), Ok(()));
+    // the parser accepts empty parts
+    assert_eq!(KeyPath::from("a..b").validate(opts), Ok(()));
+    for key in ["", ".", ".a", "a.", "a b", "a/b"] {
+        assert_eq!(
+            KeyPath::from(key).validate(opts),
+            Err(KeyError {
+                key: key.to_string()
+            })
+        );
+    }
+    assert_eq!(KeyPath::from("a/b").validate(more_keys), Ok(()));
+    assert!(KeyPath::from("a#b").validate(more_keys).is_err());
+}
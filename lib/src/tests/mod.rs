@@ -44,7 +44,15 @@ macro_rules! cni_test (
 );
 
 mod api;
+mod diff;
+mod env;
+mod glob;
+mod index;
+mod key;
+mod mutate;
 mod serializer;
+mod typed;
+mod value;
 
 mod core {
     cni_test!(bareword01, "cni/tests/core/bareword/01");
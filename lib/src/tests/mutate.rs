@@ -0,0 +1,90 @@
+use crate::{CniExt, CniExtMut};
+use std::collections::{BTreeMap, HashMap};
+
+fn test_map() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    map.insert("a".into(), "a".into());
+    map.insert("a.b".into(), "b".into());
+    map.insert("a.b.c".into(), "c".into());
+    map.insert("ab.c".into(), "d".into());
+    map.insert("x".into(), "x".into());
+    map
+}
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn insert_section() {
+    let mut data = test_map();
+    let sub = data.sub_tree("a");
+    data.insert_section("copy", sub.clone());
+    assert_eq!(data.sub_tree("copy"), sub);
+
+    // the top level section does not change the keys
+    let mut data = HashMap::new();
+    data.insert_section("", test_map());
+    assert_eq!(data, test_map());
+
+    let mut data = map(&[("s.k", "old")]);
+    data.insert_section("s", vec![("k", "new".to_string())]);
+    assert_eq!(data, map(&[("s.k", "new")]));
+}
+
+#[test]
+fn remove_section() {
+    let mut data = test_map();
+    let removed = data.remove_section("a");
+    assert_eq!(removed, map(&[("b", "b"), ("b.c", "c")]));
+    // the key with the name of the section and similar keys stay
+    assert_eq!(data, map(&[("a", "a"), ("ab.c", "d"), ("x", "x")]));
+
+    let mut data = test_map();
+    assert_eq!(data.remove_section(""), test_map());
+    assert!(data.is_empty());
+}
+
+#[test]
+fn rename_section() {
+    let mut data = test_map();
+    assert_eq!(data.rename_section("a", "z.y"), 2);
+    assert_eq!(
+        data,
+        map(&[
+            ("a", "a"),
+            ("z.y.b", "b"),
+            ("z.y.b.c", "c"),
+            ("ab.c", "d"),
+            ("x", "x")
+        ])
+    );
+
+    // moving into a subsection of itself
+    let mut data = map(&[("a.b", "1")]);
+    assert_eq!(data.rename_section("a", "a.a"), 1);
+    assert_eq!(data, map(&[("a.a.b", "1")]));
+
+    let mut data = test_map();
+    assert_eq!(data.rename_section("a", "a"), 2);
+    assert_eq!(data, test_map());
+    assert_eq!(data.rename_section("missing", "other"), 0);
+}
+
+#[test]
+fn merge_with() {
+    let mut data = test_map().into_iter().collect::<BTreeMap<_, _>>();
+    let mut conflicts = Vec::new();
+    data.merge_with(map(&[("a", "new"), ("new", "n")]), |key, ours, theirs| {
+        conflicts.push((key.to_string(), ours.clone(), theirs));
+        ours
+    });
+
+    assert_eq!(conflicts, vec![("a".into(), "a".into(), "new".into())]);
+    assert_eq!(data.get("a").map(String::as_str), Some("a"));
+    assert_eq!(data.get("new").map(String::as_str), Some("n"));
+    assert_eq!(data.len(), test_map().len() + 1);
+}
@@ -9,7 +9,7 @@ macro_rules! cni_test (
         fn $name(){
             let data = serde_json::from_str(include_str!(concat!($path, ".json"))).unwrap();
             assert_eq!(
-                crate::from_str(&crate::to_str(&data)).unwrap(),
+                crate::from_str(&crate::to_str(&data).unwrap()).unwrap(),
                 data
             );
         }
@@ -20,7 +20,7 @@ macro_rules! cni_test (
             let data = serde_json::from_str(include_str!(concat!($path, ".json"))).unwrap();
             assert_eq!(
                 crate::from_str_opts(
-                    &crate::to_str(&data),
+                    &crate::to_str_opts(&data, crate::Opts{ ini: true, more_keys: false }).unwrap(),
                     crate::Opts{ ini: true, more_keys: false },
                 ).unwrap(),
                 data
@@ -33,7 +33,7 @@ macro_rules! cni_test (
             let data = serde_json::from_str(include_str!(concat!($path, ".json"))).unwrap();
             assert_eq!(
                 crate::from_str_opts(
-                    &crate::to_str(&data),
+                    &crate::to_str_opts(&data, crate::Opts{ ini: false, more_keys: true }).unwrap(),
                     crate::Opts{ ini: false, more_keys: true },
                 ).unwrap(),
                 data
@@ -106,7 +106,7 @@ fn section() {
     let mut map = HashMap::new();
     map.insert("a.b", "c");
 
-    assert_eq!(&crate::to_str(map), "[a]\nb = c\n");
+    assert_eq!(crate::to_str(map).unwrap(), "[a]\nb = c\n");
 }
 
 #[test]
@@ -114,7 +114,7 @@ fn multi_section() {
     let mut map = HashMap::new();
     map.insert("a.b.c".to_string(), "d".to_string());
 
-    assert_eq!(&crate::to_str(map), "[a]\nb.c = d\n");
+    assert_eq!(crate::to_str(map).unwrap(), "[a]\nb.c = d\n");
 }
 
 #[test]
@@ -124,7 +124,7 @@ fn section_nonalphabetical() {
     map.insert("ccc", "without section header");
 
     assert_eq!(
-        &crate::to_str(map),
+        crate::to_str(map).unwrap(),
         "ccc = without section header\n[a]\nb = with section header\n"
     );
 }
@@ -132,7 +132,7 @@ fn section_nonalphabetical() {
 #[test]
 fn multi_value() {
     assert_eq!(
-        &crate::to_str(vec![("a", "b"), ("c", "d"),]),
+        crate::to_str(vec![("a", "b"), ("c", "d"),]).unwrap(),
         "a = b\nc = d\n"
     );
 }
@@ -142,23 +142,23 @@ fn value_backtick() {
     let mut map = BTreeMap::new();
     map.insert("a", "backtick`d");
 
-    assert_eq!(&crate::to_str(map), "a = `backtick``d`\n");
+    assert_eq!(crate::to_str(map).unwrap(), "a = `backtick``d`\n");
 }
 
 #[test]
 fn value_vertical_whitespace() {
     assert_eq!(
-        &crate::to_str(vec![("a", "multi\nline")]),
+        crate::to_str(vec![("a", "multi\nline")]).unwrap(),
         "a = `multi\nline`\n"
     );
 
     assert_eq!(
-        &crate::to_str(vec![("a", "multi\r\nline")]),
+        crate::to_str(vec![("a", "multi\r\nline")]).unwrap(),
         "a = `multi\r\nline`\n"
     );
 
     assert_eq!(
-        &crate::to_str(vec![("a", "multi\u{b}line")]),
+        crate::to_str(vec![("a", "multi\u{b}line")]).unwrap(),
         "a = `multi\u{b}line`\n"
     );
 }
@@ -166,17 +166,333 @@ fn value_vertical_whitespace() {
 #[test]
 fn value_comment_symbol() {
     assert_eq!(
-        &crate::to_str(vec![("a", "sharp#sign")]),
+        crate::to_str(vec![("a", "sharp#sign")]).unwrap(),
         "a = `sharp#sign`\n"
     );
 
+    // semicolons only start comments with the ini option
     assert_eq!(
-        &crate::to_str(vec![("a", "semi;colon")]),
+        crate::to_str(vec![("a", "semi;colon")]).unwrap(),
+        "a = semi;colon\n"
+    );
+
+    assert_eq!(
+        crate::to_str_opts(
+            vec![("a", "semi;colon")],
+            crate::Opts {
+                ini: true,
+                more_keys: false
+            }
+        )
+        .unwrap(),
         "a = `semi;colon`\n"
     );
 }
 
+#[test]
+fn value_whitespace() {
+    assert_eq!(
+        crate::to_str(vec![("a", " padded "), ("b", "`raw")]).unwrap(),
+        "a = ` padded `\nb = ```raw`\n"
+    );
+}
+
+#[test]
+fn invalid_keys() {
+    use crate::error::KeyError;
+    use crate::{CniWriter, Opts};
+
+    for key in ["", "a b", ".a", "a.", "a=b", "[a]", "a#b"] {
+        assert_eq!(
+            crate::to_str(vec![(key, "value")]),
+            Err(KeyError {
+                key: key.to_string()
+            })
+        );
+    }
+
+    let more_keys = Opts {
+        ini: false,
+        more_keys: true,
+    };
+    assert_eq!(
+        crate::to_str_opts(vec![("a/b", "c")], more_keys).unwrap(),
+        "a/b = c\n"
+    );
+    assert!(crate::to_str(vec![("a/b", "c")]).is_err());
+
+    // nothing is written if any key is invalid
+    let mut buf = Vec::new();
+    let err = crate::to_writer(&mut buf, vec![("a", "b"), ("c d", "e")]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(buf.is_empty());
+
+    let mut writer = CniWriter::new(Vec::new());
+    assert!(writer.begin_section("a.").is_err());
+    assert!(writer.write_pair("c d", "e").is_err());
+    assert!(writer.into_inner().is_empty());
+}
+
+#[test]
+fn empty_parts() {
+    use crate::{Headers, SerializerOptions};
+
+    // the parser accepts keys with empty parts, they are never split at an
+    // empty part
+    let src = "a..b = 1\nx.y..z = 2\nx.y.w = 3\nx..v = 4\n";
+    let map = crate::from_str(src).unwrap();
+    assert_eq!(
+        crate::from_str(&crate::to_str(&map).unwrap()),
+        Ok(map.clone())
+    );
+    for headers in [Headers::Never, Headers::Always, Headers::Threshold(2)] {
+        let opts = SerializerOptions {
+            headers,
+            max_depth: usize::MAX,
+            ..SerializerOptions::default()
+        };
+        let cni = crate::to_str_with(&map, &opts).unwrap();
+        assert_eq!(crate::from_str(&cni), Ok(map.clone()), "{cni}");
+    }
+
+    let deep = SerializerOptions {
+        max_depth: usize::MAX,
+        ..SerializerOptions::default()
+    };
+    assert_eq!(
+        crate::to_str_with(&map, &deep).unwrap(),
+        "a..b = 1\nx..v = 4\n[x]\ny..z = 2\n[x.y]\nw = 3\n"
+    );
+}
+
 #[test]
 fn empty_value() {
-    assert_eq!(&crate::to_str(std::iter::once(("a", ""))), "a = #empty\n");
+    assert_eq!(
+        crate::to_str(std::iter::once(("a", ""))).unwrap(),
+        "a = #empty\n"
+    );
+}
+
+fn options_data() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("top", "1"),
+        ("a.b.c", "2"),
+        ("a.b.d", "3"),
+        ("a.e", "4"),
+        ("x.y", "5"),
+    ]
+}
+
+#[test]
+fn options_headers() {
+    use crate::{Headers, SerializerOptions};
+
+    let never = SerializerOptions {
+        headers: Headers::Never,
+        spacing: false,
+        ..SerializerOptions::default()
+    };
+    assert_eq!(
+        crate::to_str_with(options_data(), &never).unwrap(),
+        "top=1\na.b.c=2\na.b.d=3\na.e=4\nx.y=5\n"
+    );
+
+    let deep = SerializerOptions {
+        max_depth: usize::MAX,
+        ..SerializerOptions::default()
+    };
+    assert_eq!(
+        crate::to_str_with(options_data(), &deep).unwrap(),
+        "top = 1\n[a]\ne = 4\n[a.b]\nc = 2\nd = 3\n[x]\ny = 5\n"
+    );
+
+    let threshold = SerializerOptions {
+        headers: Headers::Threshold(2),
+        max_depth: usize::MAX,
+        blank_line: true,
+        ..SerializerOptions::default()
+    };
+    assert_eq!(
+        crate::to_str_with(options_data(), &threshold).unwrap(),
+        "top = 1\na.e = 4\nx.y = 5\n\n[a.b]\nc = 2\nd = 3\n"
+    );
+
+    // the section "a" is large enough if "a.b" is not used
+    let threshold = SerializerOptions {
+        headers: Headers::Threshold(3),
+        max_depth: usize::MAX,
+        ..SerializerOptions::default()
+    };
+    assert_eq!(
+        crate::to_str_with(options_data(), &threshold).unwrap(),
+        "top = 1\nx.y = 5\n[a]\nb.c = 2\nb.d = 3\ne = 4\n"
+    );
+}
+
+#[test]
+fn options_sort() {
+    use crate::{SerializerOptions, Sort};
+
+    let data = vec![
+        ("b.x", "1"),
+        ("a", "2"),
+        ("b.y", "3"),
+        ("c.z", "4"),
+        ("b.w", "5"),
+    ];
+
+    let keys = SerializerOptions {
+        sort: Sort::Keys,
+        ..SerializerOptions::default()
+    };
+    assert_eq!(
+        crate::to_str_with(data.clone(), &keys).unwrap(),
+        "a = 2\n[b]\nw = 5\nx = 1\ny = 3\n[c]\nz = 4\n"
+    );
+
+    // the top level section is entered again with an empty header
+    let unsorted = SerializerOptions {
+        sort: Sort::Unsorted,
+        ..SerializerOptions::default()
+    };
+    let cni = crate::to_str_with(data.clone(), &unsorted).unwrap();
+    assert_eq!(
+        cni,
+        "[b]\nx = 1\n[]\na = 2\n[b]\ny = 3\n[c]\nz = 4\n[b]\nw = 5\n"
+    );
+    assert_eq!(
+        crate::from_str(&cni).unwrap(),
+        data.into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>()
+    );
+}
+
+#[test]
+fn writer() {
+    use crate::{CniWriter, SerializerOptions};
+
+    let opts = SerializerOptions {
+        blank_line: true,
+        spacing: false,
+        ..SerializerOptions::default()
+    };
+    let mut writer = CniWriter::with_options(Vec::new(), opts);
+    writer.write_comment("first\r\n\nsecond\n").unwrap();
+    writer.begin_section("a.b").unwrap();
+    assert_eq!(writer.section(), "a.b");
+    writer.write_pair("c", "raw`value").unwrap();
+    writer.begin_section("").unwrap();
+    writer.write_pair("d", "").unwrap();
+
+    let cni = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        cni,
+        "# first\n#\n# second\n\n[a.b]\nc=`raw``value`\n\n[]\nd=#empty\n"
+    );
+
+    let mut map = HashMap::new();
+    map.insert("a.b.c".to_string(), "raw`value".to_string());
+    map.insert("d".to_string(), String::new());
+    assert_eq!(crate::from_str(&cni).unwrap(), map);
+}
+
+#[test]
+fn to_writer() {
+    let data = vec![("a.b", "c"), ("d", "e")];
+
+    let mut buf = Vec::new();
+    crate::to_writer(&mut buf, data.clone()).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        crate::to_str(data).unwrap()
+    );
+}
+
+#[test]
+fn comments() {
+    use crate::{CniWriter, Opts, SerializerOptions};
+
+    let mut opts = SerializerOptions {
+        blank_line: true,
+        ..SerializerOptions::default()
+    };
+    opts.comments
+        .insert("name".into(), "the name of the service".into());
+    opts.comments
+        .insert("server".into(), "network settings".into());
+    opts.comments
+        .insert("server.port".into(), "TCP port\nmust be free".into());
+    opts.comments.insert("missing".into(), "not written".into());
+
+    let data = vec![
+        ("name", "web"),
+        ("server.port", "80"),
+        ("server.host", "::"),
+    ];
+    let cni = crate::to_str_with(data.clone(), &opts).unwrap();
+    assert_eq!(
+        cni,
+        "# the name of the service\nname = web\n\n# network settings\n[server]\nhost = ::\n# TCP port\n# must be free\nport = 80\n"
+    );
+    assert_eq!(
+        crate::from_str(&cni).unwrap(),
+        data.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>()
+    );
+
+    opts.cni = Opts {
+        ini: true,
+        more_keys: false,
+    };
+    let mut writer = CniWriter::with_options(Vec::new(), opts);
+    writer.write_comment("generated").unwrap();
+    writer.write_pair("name", "web").unwrap();
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        "; generated\n; the name of the service\nname = web\n"
+    );
+}
+
+/// Builds a valid key from arbitrary data, the parts are joined with dots.
+/// Only parts in the middle may be empty.
+fn arbitrary_key(parts: &[Vec<u8>], more_keys: bool) -> Option<String> {
+    let chars: &[char] = if more_keys {
+        &['a', 'Z', '0', '-', '_', '/', ':', 'ä']
+    } else {
+        &['a', 'Z', '0', '-', '_']
+    };
+    let start = parts.iter().position(|part| !part.is_empty())?;
+    let end = parts.iter().rposition(|part| !part.is_empty())? + 1;
+    let parts = parts[start..end]
+        .iter()
+        .map(|part| {
+            part.iter()
+                .map(|b| chars[usize::from(*b) % chars.len()])
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    Some(parts.join("."))
+}
+
+#[test]
+fn round_trip() {
+    use crate::Opts;
+
+    /// Key parts and values, the keys are made valid by [`arbitrary_key`].
+    type Data = Vec<(Vec<Vec<u8>>, String)>;
+
+    fn prop(data: Data, ini: bool, more_keys: bool) -> bool {
+        let opts = Opts { ini, more_keys };
+        let map = data
+            .into_iter()
+            .filter_map(|(key, value)| Some((arbitrary_key(&key, more_keys)?, value)))
+            .collect::<HashMap<_, _>>();
+        let cni = crate::to_str_opts(&map, opts).unwrap();
+        crate::from_str_opts(&cni, opts) == Ok(map)
+    }
+
+    let prop: fn(Data, bool, bool) -> bool = prop;
+    quickcheck::QuickCheck::new().quickcheck(prop);
 }
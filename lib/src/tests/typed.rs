@@ -0,0 +1,133 @@
+use crate::error::{ValueError, ValueKind};
+use crate::CniTypedExt;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+fn test_map() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    map.insert("bool".into(), "Off".into());
+    map.insert("int".into(), "-42".into());
+    map.insert("duration".into(), "1d 2h3m4s5ms".into());
+    map.insert("size".into(), "512KiB".into());
+    map.insert("list".into(), "a;b; c".into());
+    map.insert("text".into(), "not a number".into());
+    map
+}
+
+#[test]
+fn bool() {
+    assert_eq!(test_map().get_bool("bool"), Ok(Some(false)));
+    assert_eq!(test_map().get_bool("missing"), Ok(None));
+    assert_eq!(
+        test_map().get_bool("text"),
+        Err(ValueError {
+            key: "text".into(),
+            pos: None,
+            kind: ValueKind::Bool
+        })
+    );
+}
+
+/// Keeps the positions of the values like a caller that has parser
+/// positions would.
+struct Positioned(HashMap<String, (String, (usize, usize))>);
+
+impl CniTypedExt for Positioned {
+    fn get_str(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|(value, _)| value.as_str())
+    }
+
+    fn get_pos(&self, key: &str) -> Option<(usize, usize)> {
+        self.0.get(key).map(|(_, pos)| *pos)
+    }
+}
+
+#[test]
+fn position() {
+    let mut parser = crate::CniParser::from("[server]\nport = 80\n  enabled = maybe\n");
+    let mut map = HashMap::new();
+    while let Some(pair) = parser.next() {
+        let (key, value) = pair.unwrap();
+        map.insert(key, (value, parser.last_pos().unwrap()));
+    }
+    let map = Positioned(map);
+
+    assert_eq!(map.get_int("server.port"), Ok(Some(80)));
+    let err = map.get_bool("server.enabled").unwrap_err();
+    assert_eq!(err.pos, Some((3, 13)));
+    assert_eq!(
+        err.to_string(),
+        "line 3:13: server.enabled: malformed boolean"
+    );
+
+    // positions can also be added by the caller
+    let err = test_map().get_bool("text").unwrap_err().with_pos(1, 2);
+    assert_eq!(err.to_string(), "line 1:2: text: malformed boolean");
+}
+
+#[test]
+fn parsed() {
+    assert_eq!(test_map().get_int("int"), Ok(Some(-42)));
+    assert_eq!(test_map().get_parsed::<u8>("int").unwrap_err().key, "int");
+    assert_eq!(
+        test_map()
+            .get_parsed::<f64>("text")
+            .unwrap_err()
+            .to_string(),
+        "text: invalid float literal"
+    );
+}
+
+#[test]
+fn duration() {
+    assert_eq!(
+        test_map().get_duration("duration"),
+        Ok(Some(Duration::from_millis(
+            ((24 + 2) * 60 * 60 + 3 * 60 + 4) * 1000 + 5
+        )))
+    );
+    assert_eq!(
+        crate::convert::parse_duration("10 minutes"),
+        Err(ValueKind::Duration)
+    );
+    assert_eq!(
+        crate::convert::parse_duration("5"),
+        Err(ValueKind::Duration)
+    );
+    assert_eq!(crate::convert::parse_duration("0"), Ok(Duration::ZERO));
+
+    for duration in [
+        Duration::ZERO,
+        Duration::new(2 * 24 * 60 * 60 + 1, 1),
+        Duration::from_micros(1500),
+        Duration::MAX,
+    ] {
+        let formatted = crate::convert::format_duration(duration);
+        assert_eq!(crate::convert::parse_duration(&formatted), Ok(duration));
+    }
+}
+
+#[test]
+fn size() {
+    assert_eq!(test_map().get_size("size"), Ok(Some(512 * 1024)));
+    assert_eq!(crate::convert::parse_size("1G"), Ok(1_000_000_000));
+    assert_eq!(crate::convert::parse_size("1 PiB"), Err(ValueKind::Size));
+    assert_eq!(
+        crate::convert::parse_size("99999999999TiB"),
+        Err(ValueKind::Size)
+    );
+
+    for size in [0, 1, 1023, 1024, 3 << 30, u64::MAX] {
+        let formatted = crate::convert::format_size(size);
+        assert_eq!(crate::convert::parse_size(&formatted), Ok(size));
+    }
+    assert_eq!(crate::convert::format_size(1 << 40), "1TiB");
+}
+
+#[test]
+fn list() {
+    let map = test_map().into_iter().collect::<BTreeMap<_, _>>();
+
+    assert_eq!(map.get_list("list", ';'), Some(vec!["a", "b", "c"]));
+    assert_eq!(map.get_list("missing", ';'), None);
+}
@@ -0,0 +1,90 @@
+use crate::{Table, Value};
+use std::collections::BTreeMap;
+
+fn test_table() -> Table {
+    "a = 1\na.b = 2\n[server]\nhost = localhost\nport = 8080\n"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn index() {
+    let mut table = test_table();
+
+    assert_eq!(table["a"].as_str(), Some("1"));
+    assert_eq!(table["a"]["b"], Value::from("2"));
+    assert_eq!(table["server.port"].as_str(), Some("8080"));
+    assert_eq!(table["server"].as_table().map(Table::len), Some(2));
+    assert!(table.get("server.port.x").is_none());
+    assert!(table.get("missing").is_none());
+
+    table["server"]["port"] = Value::from("80");
+    assert_eq!(table.get("server.port").and_then(Value::as_str), Some("80"));
+}
+
+#[test]
+#[should_panic(expected = "key 'server.user' does not exist")]
+fn index_missing() {
+    let _ = &test_table()["server.user"];
+}
+
+#[test]
+fn insert_remove() {
+    let mut table = Table::new();
+    assert_eq!(table.insert("a.b", "1"), None);
+    // a value becomes a section with a value and the other way around
+    assert_eq!(table.insert("a.b.c", "2"), None);
+    assert_eq!(table.insert("a", "3"), None);
+    assert_eq!(table.insert("a.b", "4"), Some("1".into()));
+
+    assert_eq!(table["a"].as_str(), Some("3"));
+    assert_eq!(table["a.b"].as_str(), Some("4"));
+    assert_eq!(table["a.b.c"].as_str(), Some("2"));
+
+    assert_eq!(table.remove("a.b.c"), Some(Value::from("2")));
+    assert_eq!(table.remove("a.x"), None);
+    assert!(table.remove("a").is_some());
+    assert!(table.is_empty());
+}
+
+#[test]
+fn flat_map() {
+    let map = crate::from_str("a = 1\na.b = 2\n[server]\nhost = localhost\nport = 8080\n")
+        .unwrap()
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+
+    assert_eq!(test_table().to_map(), map);
+    assert_eq!(map.iter().collect::<Table>(), test_table());
+}
+
+#[test]
+fn to_cni() {
+    assert_eq!(
+        test_table().to_cni().unwrap(),
+        "a = 1\n[a]\nb = 2\n[server]\nhost = localhost\nport = 8080\n"
+    );
+
+    // tables can hold keys that can not be written
+    let mut table = test_table();
+    table.insert("a b", "3");
+    assert_eq!(table.to_cni().unwrap_err().key, "a b");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let json = serde_json::json!({
+        "a": { "": "1", "b": "2" },
+        "server": { "host": "localhost", "port": "8080" },
+    });
+
+    assert_eq!(serde_json::to_value(test_table()).unwrap(), json);
+    assert_eq!(serde_json::from_value::<Table>(json).unwrap(), test_table());
+    assert_eq!(
+        serde_json::from_str::<Table>(r#"{ "n": 1, "list": [true, null] }"#).unwrap(),
+        vec![("n", "1"), ("list.0", "true"), ("list.1", "")]
+            .into_iter()
+            .collect()
+    );
+}
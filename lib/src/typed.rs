@@ -0,0 +1,156 @@
+//! This module supplies accessors that convert values to other types.
+
+use crate::convert;
+use crate::error::{ValueError, ValueKind};
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Provides accessors that convert values to other types, using the
+/// conversions from the [`convert`] module.
+///
+/// All accessors return `Ok(None)` if the key does not exist and an `Err`
+/// containing the key if the value could not be converted. The error also
+/// contains the position of the value if [`CniTypedExt::get_pos`] knows it.
+///
+/// You can use the implementations for [`HashMap`] and [`BTreeMap`] by
+/// importing this trait.
+///
+/// # Examples
+/// ```
+/// use cni_format::CniTypedExt;
+/// use std::time::Duration;
+///
+/// let cni = r"
+/// [server]
+/// enabled = yes
+/// port = 8080
+/// timeout = 1m30s
+/// hosts = a.example, b.example
+/// ";
+///
+/// let parsed = cni_format::from_str(&cni).expect("could not parse CNI");
+///
+/// assert_eq!(parsed.get_bool("server.enabled"), Ok(Some(true)));
+/// assert_eq!(parsed.get_parsed::<u16>("server.port"), Ok(Some(8080)));
+/// assert_eq!(parsed.get_duration("server.timeout"), Ok(Some(Duration::from_secs(90))));
+/// assert_eq!(parsed.get_list("server.hosts", ','), Some(vec!["a.example", "b.example"]));
+/// assert_eq!(parsed.get_bool("server.missing"), Ok(None));
+///
+/// let err = parsed.get_bool("server.port").unwrap_err();
+/// assert_eq!(err.to_string(), "server.port: malformed boolean");
+/// ```
+pub trait CniTypedExt {
+    /// Returns the value for the specified key as a string.
+    fn get_str(&self, key: &str) -> Option<&str>;
+
+    /// Returns the line and column the value for the specified key was read
+    /// from, which is used for errors. Implement this if you keep the
+    /// positions from [`CniParser::last_pos`](crate::CniParser::last_pos).
+    /// The default implementation returns `None`.
+    fn get_pos(&self, _key: &str) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Applies a conversion to the value for the specified key.
+    ///
+    /// # Errors
+    /// Returns an `Err` with the key and the position of the value if the
+    /// conversion fails.
+    fn get_with<T, F>(&self, key: &str, f: F) -> Result<Option<T>, ValueError>
+    where
+        F: FnOnce(&str) -> Result<T, ValueKind>,
+    {
+        self.get_str(key)
+            .map(|value| {
+                f(value).map_err(|kind| ValueError {
+                    key: key.to_string(),
+                    pos: self.get_pos(key),
+                    kind,
+                })
+            })
+            .transpose()
+    }
+
+    /// Returns the value for the specified key as a boolean, see
+    /// [`convert::parse_bool`] for the accepted values.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value is not a boolean.
+    fn get_bool(&self, key: &str) -> Result<Option<bool>, ValueError> {
+        self.get_with(key, convert::parse_bool)
+    }
+
+    /// Returns the value for the specified key as an integer.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value is not an integer.
+    fn get_int(&self, key: &str) -> Result<Option<i64>, ValueError> {
+        self.get_parsed(key)
+    }
+
+    /// Returns the value for the specified key parsed with [`FromStr`].
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value could not be parsed, containing the
+    /// message of the parser.
+    fn get_parsed<T>(&self, key: &str) -> Result<Option<T>, ValueError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.get_with(key, |value| {
+            value
+                .parse()
+                .map_err(|e: T::Err| ValueKind::Parse(e.to_string()))
+        })
+    }
+
+    /// Returns the value for the specified key as a duration, see
+    /// [`convert::parse_duration`] for the accepted values.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value is not a duration.
+    fn get_duration(&self, key: &str) -> Result<Option<Duration>, ValueError> {
+        self.get_with(key, convert::parse_duration)
+    }
+
+    /// Returns the value for the specified key as a size in bytes, see
+    /// [`convert::parse_size`] for the accepted values.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value is not a size.
+    fn get_size(&self, key: &str) -> Result<Option<u64>, ValueError> {
+        self.get_with(key, convert::parse_size)
+    }
+
+    /// Returns the value for the specified key split at the separator, see
+    /// [`convert::split_list`].
+    fn get_list(&self, key: &str, sep: char) -> Option<Vec<&str>> {
+        self.get_str(key)
+            .map(|value| convert::split_list(value, sep))
+    }
+}
+
+impl<K, V, S> CniTypedExt for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+    S: BuildHasher,
+{
+    fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).map(AsRef::as_ref)
+    }
+}
+
+impl<K, V> CniTypedExt for BTreeMap<K, V>
+where
+    K: Borrow<str> + Ord,
+    V: AsRef<str>,
+{
+    fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).map(AsRef::as_ref)
+    }
+}
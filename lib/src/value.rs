@@ -0,0 +1,355 @@
+//! This module supplies a tree representation of CNI data.
+
+use crate::KeyPath;
+use std::collections::{btree_map, BTreeMap};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// A value or section of CNI data, to inspect data without knowing its
+/// structure in advance.
+///
+/// # Examples
+/// ```
+/// use cni_format::{Table, Value};
+///
+/// let cni = r"
+/// [server]
+/// host = localhost
+/// port = 8080
+/// ";
+///
+/// let table: Table = cni.parse().expect("could not parse CNI");
+///
+/// assert_eq!(table["server"]["port"], Value::from("8080"));
+/// assert_eq!(table["server.host"].as_str(), Some("localhost"));
+/// assert!(table.get("server.user").is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// A value without any keys below it.
+    String(String),
+    /// A section, which may also have a value itself.
+    Table(Table),
+}
+
+impl Value {
+    /// Returns the string value, which is also possible for a table that
+    /// has a value itself.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            Self::Table(table) => table.value(),
+        }
+    }
+
+    /// Returns the table if this is a section.
+    #[must_use]
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Self::String(_) => None,
+            Self::Table(table) => Some(table),
+        }
+    }
+
+    /// Returns the table if this is a section.
+    #[must_use]
+    pub fn as_table_mut(&mut self) -> Option<&mut Table> {
+        match self {
+            Self::String(_) => None,
+            Self::Table(table) => Some(table),
+        }
+    }
+
+    /// Returns the value for the specified key, which may contain dots to
+    /// get values from subsections. Returns `None` if the key does not exist
+    /// or this is not a section.
+    #[must_use]
+    pub fn get<'k>(&self, key: impl Into<KeyPath<'k>>) -> Option<&Value> {
+        self.as_table().and_then(|table| table.get(key))
+    }
+
+    /// Returns the value for the specified key mutably, see [`Value::get`].
+    #[must_use]
+    pub fn get_mut<'k>(&mut self, key: impl Into<KeyPath<'k>>) -> Option<&mut Value> {
+        self.as_table_mut().and_then(|table| table.get_mut(key))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<Table> for Value {
+    fn from(table: Table) -> Self {
+        Self::Table(table)
+    }
+}
+
+/// Indexes the value of a section by key, see [`Table`].
+///
+/// # Panics
+/// Panics if this is not a section or the key does not exist.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key)
+            .unwrap_or_else(|| panic!("key '{key}' does not exist"))
+    }
+}
+
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        self.get_mut(key)
+            .unwrap_or_else(|| panic!("key '{key}' does not exist"))
+    }
+}
+
+/// A section of CNI data, mapping keys to values or subsections.
+///
+/// CNI allows a key to have a value and also be a section, e.g. `a = 1` and
+/// `a.b = 2`. In this case, the subsection `a` has the value `1` itself, so
+/// converting from and to the flat representation does not lose data.
+///
+/// Tables are sorted by key. They can be indexed with keys that contain
+/// dots, which panics if the key does not exist. Use [`Table::get`] instead
+/// if that is not wanted.
+///
+/// # Examples
+/// ```
+/// use cni_format::Table;
+///
+/// let table: Table = vec![("a", "1"), ("a.b", "2")].into_iter().collect();
+///
+/// assert_eq!(table["a"].as_str(), Some("1"));
+/// assert_eq!(table["a.b"].as_str(), Some("2"));
+/// assert_eq!(table["a"]["b"].as_str(), Some("2"));
+///
+/// let map = table.to_map();
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map["a.b"], "2");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Table {
+    value: Option<String>,
+    entries: BTreeMap<String, Value>,
+}
+
+impl Table {
+    /// Creates an empty table.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of the section itself, if it has one.
+    #[must_use]
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// Sets the value of the section itself, returning the previous one.
+    pub fn set_value(&mut self, value: Option<String>) -> Option<String> {
+        std::mem::replace(&mut self.value, value)
+    }
+
+    /// Returns the value for the specified key, which may contain dots to
+    /// get values from subsections.
+    #[must_use]
+    pub fn get<'k>(&self, key: impl Into<KeyPath<'k>>) -> Option<&Value> {
+        let key = key.into();
+        match key.split_first()? {
+            (name, rest) if rest.is_root() => self.entries.get(name),
+            (name, rest) => self.entries.get(name)?.get(rest),
+        }
+    }
+
+    /// Returns the value for the specified key mutably, see [`Table::get`].
+    #[must_use]
+    pub fn get_mut<'k>(&mut self, key: impl Into<KeyPath<'k>>) -> Option<&mut Value> {
+        let key = key.into();
+        match key.split_first()? {
+            (name, rest) if rest.is_root() => self.entries.get_mut(name),
+            (name, rest) => self.entries.get_mut(name)?.get_mut(rest),
+        }
+    }
+
+    /// Inserts a value for the specified key, which may contain dots to
+    /// insert into subsections. Missing sections are created. Returns the
+    /// previous value of the key, if it had one.
+    /// The empty key sets the value of the section itself.
+    pub fn insert<'k, V: Into<String>>(
+        &mut self,
+        key: impl Into<KeyPath<'k>>,
+        value: V,
+    ) -> Option<String> {
+        let value = value.into();
+        let key = key.into();
+        let Some((name, rest)) = key.split_first() else {
+            return self.set_value(Some(value));
+        };
+        match (name, rest) {
+            (key, rest) if rest.is_root() => match self.entries.get_mut(key) {
+                Some(Value::Table(table)) => table.set_value(Some(value)),
+                Some(Value::String(own)) => Some(std::mem::replace(own, value)),
+                None => {
+                    self.entries.insert(key.to_string(), Value::String(value));
+                    None
+                }
+            },
+            (name, rest) => {
+                let entry = self
+                    .entries
+                    .entry(name.to_string())
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if let Value::String(own) = entry {
+                    // the value becomes a section with a value
+                    *entry = Value::Table(Table {
+                        value: Some(std::mem::take(own)),
+                        entries: BTreeMap::new(),
+                    });
+                }
+                match entry {
+                    Value::Table(table) => table.insert(rest, value),
+                    Value::String(_) => unreachable!(),
+                }
+            }
+        }
+    }
+
+    /// Inserts a value or subsection directly into this section.
+    #[cfg(feature = "serde")]
+    pub(crate) fn insert_value(&mut self, key: String, value: Value) {
+        self.entries.insert(key, value);
+    }
+
+    /// Removes the specified key, which may contain dots, with its value and
+    /// any subsections.
+    pub fn remove<'k>(&mut self, key: impl Into<KeyPath<'k>>) -> Option<Value> {
+        let key = key.into();
+        match key.split_first()? {
+            (name, rest) if rest.is_root() => self.entries.remove(name),
+            (name, rest) => self.entries.get_mut(name)?.as_table_mut()?.remove(rest),
+        }
+    }
+
+    /// Returns the number of keys directly in this section.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether this section has no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the keys directly in this section and their values,
+    /// sorted by key.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Value> {
+        self.entries.iter()
+    }
+
+    /// Converts the table to the flat representation, where keys in
+    /// subsections are prefixed with the section name.
+    ///
+    /// The value of the table itself is not included since it has no key.
+    #[must_use]
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::new();
+        self.flatten_into("", &mut map);
+        map
+    }
+
+    /// Formats the table as CNI format text, see
+    /// [`to_str_opts`](crate::to_str_opts). The keys are written for the
+    /// `more-keys` extension.
+    ///
+    /// # Errors
+    /// Returns an `Err` if a key can not be written even then, e.g. because
+    /// it contains a space.
+    #[cfg(any(feature = "serializer", test, doctest, doc))]
+    pub fn to_cni(&self) -> Result<String, crate::error::KeyError> {
+        let opts = crate::Opts {
+            ini: false,
+            more_keys: true,
+        };
+        crate::to_str_opts(self.to_map(), opts)
+    }
+
+    fn flatten_into(&self, prefix: &str, map: &mut BTreeMap<String, String>) {
+        for (key, value) in &self.entries {
+            let key = format!("{prefix}{key}");
+            match value {
+                Value::String(value) => {
+                    map.insert(key, value.clone());
+                }
+                Value::Table(table) => {
+                    if let Some(value) = &table.value {
+                        map.insert(key.clone(), value.clone());
+                    }
+                    table.flatten_into(&format!("{key}."), map);
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Table
+where
+    K: AsRef<str>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut table = Self::new();
+        for (key, value) in iter {
+            table.insert(key.as_ref(), value);
+        }
+        table
+    }
+}
+
+impl<'a> IntoIterator for &'a Table {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = btree_map::Iter<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromStr for Table {
+    type Err = crate::error::Error;
+
+    /// Parses CNI format text with the default options into a table, see
+    /// [`from_str`](crate::from_str).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        crate::from_str(text).map(|map| map.into_iter().collect())
+    }
+}
+
+impl Index<&str> for Table {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key)
+            .unwrap_or_else(|| panic!("key '{key}' does not exist"))
+    }
+}
+
+impl IndexMut<&str> for Table {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        self.get_mut(key)
+            .unwrap_or_else(|| panic!("key '{key}' does not exist"))
+    }
+}
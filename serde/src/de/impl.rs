@@ -1,9 +1,13 @@
-use super::{Deserializer, Tree};
+use super::{Deserializer, EmptyValues, Tree};
 use crate::error::{Error, Kind, Result};
 use serde::{
-    de::{DeserializeSeed, MapAccess, Visitor},
+    de::{
+        value::CowStrDeserializer, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, Unexpected, VariantAccess, Visitor,
+    },
     forward_to_deserialize_any,
 };
+use std::borrow::Cow;
 
 macro_rules! deserialize {
     ($deser:ident, $visit:ident, $err:ident) => {
@@ -17,6 +21,7 @@ macro_rules! deserialize {
                 Err(err) => Err(Error {
                     line,
                     col,
+                    key: None,
                     kind: Kind::$err(err),
                 }),
             }
@@ -24,23 +29,172 @@ macro_rules! deserialize {
     };
 }
 
-impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
+/// Visits the value as a string, borrowing it if possible.
+fn visit_str<'de, V>(val: Cow<'de, str>, visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    match val {
+        Cow::Borrowed(val) => visitor.visit_borrowed_str(val),
+        Cow::Owned(val) => visitor.visit_string(val),
+    }
+}
+
+/// The type a value looks like, for when the type that is deserialized does
+/// not specify what it expects.
+fn infer_type(val: &str) -> Unexpected<'_> {
+    if let Ok(int) = val.parse() {
+        Unexpected::Unsigned(int)
+    } else if let Ok(int) = val.parse() {
+        Unexpected::Signed(int)
+    } else if !val.is_empty()
+        // only accept the usual notation, no "inf" or "NaN"
+        && val.chars().all(|c| matches!(c, '0'..='9' | '+' | '-' | '.' | 'e' | 'E'))
+        && val.parse::<f64>().is_ok()
+    {
+        Unexpected::Float(val.parse().unwrap())
+    } else {
+        match val {
+            "true" => Unexpected::Bool(true),
+            "false" => Unexpected::Bool(false),
+            _ => Unexpected::Str(val),
+        }
+    }
+}
+
+/// Visits the value as the type it looks like.
+fn visit_inferred<'de, V>(val: Cow<'de, str>, visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    match infer_type(&val) {
+        Unexpected::Unsigned(int) => visitor.visit_u64(int),
+        Unexpected::Signed(int) => visitor.visit_i64(int),
+        Unexpected::Float(float) => visitor.visit_f64(float),
+        Unexpected::Bool(b) => visitor.visit_bool(b),
+        _ => visit_str(val, visitor),
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Deserializes a value without a type hint, so maps are visited as maps
+    /// and values as strings or, if `infer` is true, the type they look like.
+    fn deserialize_untyped<V>(&mut self, visitor: V, infer: bool) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.vals.pop() {
+            // errors from values that the visitor buffered are only noticed
+            // after the whole map was visited, so they are about the map
+            Some(Tree::Map(map)) => visitor.visit_map(&mut self.map(map)),
+            Some(Tree::Value(val, ..)) => {
+                if infer {
+                    visit_inferred(val, visitor)
+                } else {
+                    visit_str(val, visitor)
+                }
+            }
+            None => Err(self.expected_values()),
+        }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
-    forward_to_deserialize_any! { string str tuple tuple_struct map struct seq enum }
+    forward_to_deserialize_any! { map struct }
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let infer = self.options.infer_types;
+        self.deserialize_untyped(visitor, infer)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visit_str(self.next()?.0, visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
         match self.vals.pop() {
-            Some(Tree::Map(map)) => visitor.visit_map(&mut Deserializer::new(map)),
-            Some(Tree::Value(val, ..)) => visitor.visit_string(val),
-            None => Err(Error {
-                line: self.end.map_or(0, |x| x.0),
-                col: self.end.map_or(0, |x| x.1),
-                kind: Kind::ExpectedValues,
-            }),
+            Some(tree) => visitor.visit_seq(&mut self.seq(tree.into_seq()?)),
+            None => Err(self.expected_values()),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.vals.pop() {
+            // unit variants are just the name of the variant
+            Some(Tree::Value(val, ..)) => {
+                let val: CowStrDeserializer<Error> = val.into_deserializer();
+                visitor.visit_enum(val)
+            }
+            // other variants are a section named after the variant
+            Some(Tree::Map(map)) if map.len() == 1 => {
+                let (name, tree) = map.into_iter().next().unwrap();
+                visitor.visit_enum(Variant {
+                    name: name.clone(),
+                    de: self.map(vec![(name, tree)]),
+                })
+            }
+            Some(tree) => {
+                let (line, col) = tree.pos();
+                Err(Error {
+                    line,
+                    col,
+                    key: None,
+                    kind: Kind::Enum,
+                })
+            }
+            None => Err(self.expected_values()),
         }
     }
 
@@ -48,14 +202,11 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
     where
         V: Visitor<'de>,
     {
-        if let Some(Tree::Value(..)) = self.vals.pop() {
+        // this may also be a whole section that is ignored
+        if self.vals.pop().is_some() {
             visitor.visit_unit()
         } else {
-            Err(Error {
-                line: self.end.map_or(0, |x| x.0),
-                col: self.end.map_or(0, |x| x.1),
-                kind: Kind::ExpectedValues,
-            })
+            Err(self.expected_values())
         }
     }
 
@@ -74,15 +225,14 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
     where
         V: Visitor<'de>,
     {
-        let (mut val, line, col) = self.next()?;
+        let (val, line, col) = self.next()?;
 
-        val.make_ascii_lowercase();
-        match val.as_str() {
-            "1" | "+" | "true" | "yes" | "on" | "up" => visitor.visit_bool(true),
-            "0" | "-" | "false" | "no" | "off" | "down" => visitor.visit_bool(false),
-            _ => Err(Error {
+        match cni_format::convert::parse_bool(&val) {
+            Ok(b) => visitor.visit_bool(b),
+            Err(_) => Err(Error {
                 line,
                 col,
+                key: None,
                 kind: Kind::Bool,
             }),
         }
@@ -99,15 +249,12 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
                 Err(Error {
                     line,
                     col,
+                    key: None,
                     kind: Kind::Char,
                 })
             }
         } else {
-            Err(Error {
-                line: self.end.map_or(0, |x| x.0),
-                col: self.end.map_or(0, |x| x.1),
-                kind: Kind::ExpectedValues,
-            })
+            Err(self.expected_values())
         }
     }
 
@@ -115,24 +262,34 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_byte_buf(self.next()?.0.into())
+        match self.next()?.0 {
+            Cow::Borrowed(val) => visitor.visit_borrowed_bytes(val.as_bytes()),
+            Cow::Owned(val) => visitor.visit_byte_buf(val.into()),
+        }
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_byte_buf(self.next()?.0.into())
+        visitor.visit_byte_buf(self.next()?.0.into_owned().into())
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        if self.next()?.0.is_empty() {
-            visitor.visit_none()
-        } else {
-            visitor.visit_some(self)
+        match self.vals.last() {
+            // a missing key does not get here, the field is just missing
+            None => Err(self.expected_values()),
+            Some(Tree::Value(val, ..))
+                if val.is_empty() && self.options.empty_values == EmptyValues::None =>
+            {
+                self.vals.pop();
+                visitor.visit_none()
+            }
+            // the value is not consumed here
+            Some(_) => visitor.visit_some(self),
         }
     }
 
@@ -147,6 +304,7 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
             Err(Error {
                 line,
                 col,
+                key: None,
                 kind: Kind::Unit,
             })
         }
@@ -159,76 +317,122 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut Deserializer {
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        visitor.visit_newtype_struct(self)
-    }
-
-    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // using pop means the elements will be iterated from back to front
-        // but since they came from a hashmap with indeterminate order it
-        // does not matter anyway
-        if let Some(key) = self.keys.pop() {
-            visitor.visit_string(key)
+        // `cni_format::Value` takes values as they are written, see
+        // `serde_impl` in `cni_format`
+        if name == "$cni_format::Value" {
+            self.deserialize_untyped(visitor, false)
         } else {
-            Err(Error {
-                line: self.end.map_or(0, |x| x.0),
-                col: self.end.map_or(0, |x| x.1),
-                kind: Kind::ExpectedValues,
-            })
+            visitor.visit_newtype_struct(self)
         }
     }
 }
 
-impl<'de> MapAccess<'de> for Deserializer {
+impl<'de> MapAccess<'de> for Deserializer<'de> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
     where
         K: DeserializeSeed<'de>,
     {
-        // have to force the deserialize to deserialize an identifier
-        use serde::{de::IntoDeserializer, Deserializer};
-
-        struct KeyVisitor;
+        match self.advance() {
+            Some(key) => {
+                let key: serde::de::value::StringDeserializer<Error> = key.into_deserializer();
+                seed.deserialize(key).map(Some).map_err(|e| self.locate(e))
+            }
+            None => Ok(None),
+        }
+    }
 
-        impl Visitor<'_> for KeyVisitor {
-            type Value = String;
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = seed.deserialize(&mut *self).map_err(|e| self.locate(e))?;
+        // later errors are not about this value
+        self.current = None;
+        Ok(value)
+    }
+}
 
-            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                write!(f, "a key")
-            }
+impl<'de> SeqAccess<'de> for Deserializer<'de> {
+    type Error = Error;
 
-            fn visit_str<E>(self, s: &str) -> std::result::Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(s.to_owned())
-            }
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.advance().is_some() {
+            let value = seed.deserialize(&mut *self).map_err(|e| self.locate(e))?;
+            // later errors are not about this element
+            self.current = None;
+            Ok(Some(value))
+        } else {
+            Ok(None)
         }
+    }
 
-        let ident = self
-            .deserialize_identifier(KeyVisitor)
-            .and_then(|ident| seed.deserialize(ident.into_deserializer()));
-        match ident {
-            Ok(x) => Ok(Some(x)),
-            Err(Error {
-                kind: Kind::ExpectedValues,
-                ..
-            }) => Ok(None),
-            Err(e) => Err(e),
-        }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.vals.len())
     }
+}
 
-    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+/// Provides access to an enum variant that is represented as a section with
+/// the name of the variant.
+struct Variant<'de> {
+    name: String,
+    /// Contains the data of the variant as the only entry.
+    de: Deserializer<'de>,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'de> {
+    type Error = Error;
+    type Variant = Deserializer<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
     where
         V: DeserializeSeed<'de>,
     {
-        seed.deserialize(self)
+        let name: serde::de::value::StringDeserializer<Error> = self.name.into_deserializer();
+        let variant = seed.deserialize(name)?;
+        Ok((variant, self.de))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(mut self) -> Result<()> {
+        self.advance();
+        serde::Deserializer::deserialize_unit(&mut self, serde::de::IgnoredAny)
+            .map(|_| ())
+            .map_err(|e| self.locate(e))
+    }
+
+    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.advance();
+        seed.deserialize(&mut self).map_err(|e| self.locate(e))
+    }
+
+    fn tuple_variant<V>(mut self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.advance();
+        serde::Deserializer::deserialize_seq(&mut self, visitor).map_err(|e| self.locate(e))
+    }
+
+    fn struct_variant<V>(mut self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.advance();
+        serde::Deserializer::deserialize_any(&mut self, visitor).map_err(|e| self.locate(e))
     }
 }
@@ -2,29 +2,126 @@
 mod test;
 
 use crate::error::{Error, Kind, Result};
-use cni_format::{CniExt, CniParser};
-use serde::Deserialize;
+use cni_format::{CniParser, EnvOpts, KeyPath, Opts};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::Read;
 
 #[derive(Debug)]
-enum Tree {
-    Map(HashMap<String, Tree>),
+enum Tree<'de> {
+    /// keys and subtrees in declaration order
+    Map(Vec<(String, Tree<'de>)>),
     /// stringized value and starting position as line, column (counting from 1)
-    Value(String, usize, usize),
+    ///
+    /// The value is borrowed from the source text if possible.
+    Value(Cow<'de, str>, usize, usize),
+}
+
+impl<'de> Tree<'de> {
+    /// Returns the position of the first value in this tree.
+    fn pos(&self) -> (usize, usize) {
+        match self {
+            Self::Value(_, line, col) => (*line, *col),
+            Self::Map(map) => map
+                .iter()
+                .map(|(_, tree)| tree.pos())
+                .min()
+                .unwrap_or((0, 0)),
+        }
+    }
+
+    /// Turns this tree into the elements of a sequence. A map has to use the
+    /// indices `0`, `1`, ... as keys without gaps, a value is split at commas.
+    fn into_seq(self) -> Result<Vec<Self>> {
+        match self {
+            Self::Value(val, line, col) => Ok(split_list(val)
+                .into_iter()
+                .map(|elem| Self::Value(elem, line, col))
+                .collect()),
+            Self::Map(map) => {
+                let mut elements = map
+                    .into_iter()
+                    .map(|(key, tree)| {
+                        // only accept the canonical form so there can not be
+                        // multiple keys for the same index
+                        match key.parse::<usize>() {
+                            Ok(i) if i.to_string() == key => Ok((i, tree)),
+                            _ => {
+                                let (line, col) = tree.pos();
+                                Err(Error {
+                                    line,
+                                    col,
+                                    key: None,
+                                    kind: Kind::SeqIndex(key),
+                                })
+                            }
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                elements.sort_unstable_by_key(|(i, _)| *i);
+
+                elements
+                    .into_iter()
+                    .enumerate()
+                    .map(|(expected, (i, tree))| {
+                        if i == expected {
+                            Ok(tree)
+                        } else {
+                            let (line, col) = tree.pos();
+                            Err(Error {
+                                line,
+                                col,
+                                key: None,
+                                kind: Kind::SeqGap(expected),
+                            })
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Splits a value that is used as a sequence at commas, borrowing the
+/// elements if possible.
+fn split_list(val: Cow<'_, str>) -> Vec<Cow<'_, str>> {
+    match val {
+        Cow::Borrowed(val) => cni_format::convert::split_list(val, ',')
+            .into_iter()
+            .map(Cow::Borrowed)
+            .collect(),
+        Cow::Owned(val) => cni_format::convert::split_list(&val, ',')
+            .into_iter()
+            .map(|elem| Cow::Owned(elem.to_string()))
+            .collect(),
+    }
 }
 
 #[derive(Debug)]
-pub struct Deserializer {
+pub struct Deserializer<'de> {
+    /// Keys in reverse order, because they are taken from the back.
     keys: Vec<String>,
-    vals: Vec<Tree>,
+    /// Values in reverse order, because they are taken from the back.
+    vals: Vec<Tree<'de>>,
     end: Option<(usize, usize)>,
+    /// The dotted key of the map or sequence that is deserialized.
+    path: String,
+    /// The key and position of the value that is currently deserialized.
+    current: Option<(String, (usize, usize))>,
+    options: DeserializerOptions,
+}
+
+/// Joins a key to a dotted key, which may be empty.
+fn join(path: &str, key: &str) -> String {
+    KeyPath::from(path).join(key).into_string()
 }
 
-impl Deserializer {
-    fn new(map: HashMap<String, Tree>) -> Self {
+impl<'de> Deserializer<'de> {
+    fn new(map: Vec<(String, Tree<'de>)>, path: String, options: DeserializerOptions) -> Self {
         let end = map
-            .values()
-            .filter_map(|v| {
+            .iter()
+            .filter_map(|(_, v)| {
                 if let Tree::Value(_, line, col) = v {
                     Some((*line, *col))
                 } else {
@@ -32,20 +129,89 @@ impl Deserializer {
                 }
             })
             .max();
-        let (keys, vals): (Vec<_>, Vec<_>) = map.into_iter().unzip();
+        // elements are taken from the back
+        let (keys, vals): (Vec<_>, Vec<_>) = map.into_iter().rev().unzip();
+
+        Self {
+            keys,
+            vals,
+            end,
+            path,
+            current: None,
+            options,
+        }
+    }
+
+    /// Creates a deserializer for the map that is the current value.
+    fn map(&self, map: Vec<(String, Tree<'de>)>) -> Self {
+        Self::new(map, self.key_path(), self.options)
+    }
+
+    /// Creates a deserializer for the sequence that is the current value.
+    fn seq(&self, elements: Vec<Tree<'de>>) -> Self {
+        self.map(
+            elements
+                .into_iter()
+                .enumerate()
+                .map(|(i, tree)| (i.to_string(), tree))
+                .collect(),
+        )
+    }
+
+    /// Takes the next key, which is then used for error messages about the
+    /// next value.
+    fn advance(&mut self) -> Option<String> {
+        let key = self.keys.pop()?;
+        let pos = self.vals.last().map_or((0, 0), Tree::pos);
+        self.current = Some((key.clone(), pos));
+        Some(key)
+    }
+
+    /// The dotted key of the value that is currently deserialized.
+    fn key_path(&self) -> String {
+        match &self.current {
+            Some((key, _)) => join(&self.path, key),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Adds the key and position of the current value to an error, unless it
+    /// already came from a more specific place.
+    fn locate(&self, mut err: Error) -> Error {
+        if err.key.is_none() {
+            let mut key = self.key_path();
+            if let Kind::MissingField(field) = &err.kind {
+                key = join(&key, field);
+            }
+            if !key.is_empty() {
+                err.key = Some(key);
+            }
 
-        Self { keys, vals, end }
+            if err.line == 0 {
+                if let Some((_, (line, col))) = self.current {
+                    err.line = line;
+                    err.col = col;
+                }
+            }
+        }
+        err
+    }
+
+    /// The error for when there are no more values.
+    fn expected_values(&self) -> Error {
+        Error {
+            line: self.end.map_or(0, |x| x.0),
+            col: self.end.map_or(0, |x| x.1),
+            key: None,
+            kind: Kind::ExpectedValues,
+        }
     }
 
-    fn next(&mut self) -> Result<(String, usize, usize)> {
+    fn next(&mut self) -> Result<Located<'de>> {
         if let Some(Tree::Value(value, line, col)) = self.vals.pop() {
             Ok((value, line, col))
         } else {
-            Err(Error {
-                line: self.end.map_or(0, |x| x.0),
-                col: self.end.map_or(0, |x| x.1),
-                kind: Kind::ExpectedValues,
-            })
+            Err(self.expected_values())
         }
     }
 }
@@ -53,52 +219,339 @@ impl Deserializer {
 // actual deserialisation logic
 mod r#impl;
 
+mod options;
+pub use options::{DeserializerOptions, DuplicateKeys, EmptyValues};
+
+/// Deserializes a value from CNI format text.
+///
+/// This uses the default [`DeserializerOptions`], so keys may only appear once.
+///
+/// # Errors
+/// Returns an `Err` if the text is not valid CNI, contains a key more than
+/// once or does not match the structure of `T`.
 pub fn from_str<'de, T>(s: &'de str) -> Result<T>
 where
     T: Deserialize<'de>,
 {
-    use std::collections::hash_map::Entry;
+    DeserializerOptions::default().from_str(s)
+}
+
+/// Deserializes a value from CNI format text, using the specified
+/// [parsing options][Opts].
+///
+/// # Errors
+/// Returns an `Err` if the text is not valid CNI, contains a key more than
+/// once or does not match the structure of `T`.
+pub fn from_str_opts<'de, T>(s: &'de str, opts: Opts) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    DeserializerOptions {
+        cni: opts,
+        ..DeserializerOptions::default()
+    }
+    .from_str(s)
+}
 
-    let mut parser: CniParser<std::str::Chars<'de>> = s.into();
-    let mut data = HashMap::new();
+/// Deserializes a value from CNI format text that is read from the reader.
+///
+/// The whole input is read before it is parsed.
+///
+/// # Errors
+/// Returns an `Err` if the input could not be read or is not valid UTF-8,
+/// or for the same reasons as [`from_str`].
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    DeserializerOptions::default().from_reader(reader)
+}
+
+/// Deserializes a value from CNI format text that is read from the reader,
+/// using the specified [parsing options][Opts].
+///
+/// The whole input is read before it is parsed.
+///
+/// # Errors
+/// Returns an `Err` if the input could not be read or is not valid UTF-8,
+/// or for the same reasons as [`from_str`].
+pub fn from_reader_opts<R, T>(reader: R, opts: Opts) -> Result<T>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    DeserializerOptions {
+        cni: opts,
+        ..DeserializerOptions::default()
+    }
+    .from_reader(reader)
+}
+
+/// Deserializes a value from a key/value store, e.g. the result of
+/// [`cni_format::from_str`] after merging it with other values.
+///
+/// Since the values do not come from CNI text, errors do not have a
+/// position, i.e. line and column will be 0.
+///
+/// # Errors
+/// Returns an `Err` if the values do not match the structure of `T`.
+pub fn from_map<I, K, V, T>(map: I) -> Result<T>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+    T: DeserializeOwned,
+{
+    let mut data = Entries::default();
+    for (key, val) in map {
+        data.insert(
+            key.into(),
+            (Cow::Owned(val.into()), 0, 0),
+            DuplicateKeys::LastWins,
+        )?;
+    }
+    from_data(data, &DeserializerOptions::default())
+}
+
+/// Deserializes a value from CNI format text, with environment variables
+/// overlaid according to the given options. Values from environment
+/// variables replace values from the text.
+///
+/// Errors in values that come from environment variables do not have a
+/// position, i.e. line and column will be 0.
+///
+/// # Errors
+/// Returns an `Err` if the text is not valid CNI, contains a key more than
+/// once or the combined values do not match the structure of `T`.
+pub fn from_str_with_env<'de, T>(s: &'de str, env: &EnvOpts) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    let options = DeserializerOptions::default();
+    let mut data = parse(s, &options)?;
+    for (key, val) in env.vars() {
+        data.insert(key, (Cow::Owned(val), 0, 0), DuplicateKeys::LastWins)?;
+    }
+    from_data(data, &options)
+}
+
+/// A value and its starting position as line, column (counting from 1).
+type Located<'de> = (Cow<'de, str>, usize, usize);
+
+/// Values and their starting position in declaration order.
+#[derive(Default)]
+struct Entries<'de> {
+    /// Contains more than one value per key if they are collected.
+    order: Vec<(String, Vec<Located<'de>>)>,
+    index: HashMap<String, usize>,
+}
+
+impl<'de> Entries<'de> {
+    /// Inserts a value, handling an existing value for the same key with the
+    /// given policy. The key keeps the position of its first declaration in
+    /// the order.
+    fn insert(&mut self, key: String, val: Located<'de>, policy: DuplicateKeys) -> Result<()> {
+        if let Some(&i) = self.index.get(&key) {
+            match policy {
+                DuplicateKeys::Error => {
+                    return Err(Error {
+                        line: val.1,
+                        col: val.2,
+                        key: None,
+                        kind: Kind::DuplicateKey(key),
+                    })
+                }
+                DuplicateKeys::LastWins => self.order[i].1 = vec![val],
+                DuplicateKeys::FirstWins => (),
+                DuplicateKeys::CollectIntoSeq => self.order[i].1.push(val),
+            }
+        } else {
+            self.index.insert(key.clone(), self.order.len());
+            self.order.push((key, vec![val]));
+        }
+        Ok(())
+    }
+
+    /// Returns the values in order, collected values are turned into a
+    /// sequence by appending the indices to the key.
+    ///
+    /// Each collected value is split at commas, like a single value that is
+    /// deserialized as a sequence, so repeating a key only appends elements.
+    /// It is an error if an index is also used explicitly, e.g. `a.0`.
+    fn into_vec(self) -> Result<Vec<(String, Located<'de>)>> {
+        let Self { order, index } = self;
+        // the position of the first value of each key, for errors
+        let positions = order
+            .iter()
+            .map(|(_, vals)| (vals[0].1, vals[0].2))
+            .collect::<Vec<_>>();
+        let mut result = Vec::with_capacity(order.len());
+        for (key, mut vals) in order {
+            if vals.len() == 1 {
+                result.push((key, vals.pop().unwrap()));
+                continue;
+            }
+
+            let elements = vals.into_iter().flat_map(|(val, line, col)| {
+                split_list(val)
+                    .into_iter()
+                    .map(move |elem| (elem, line, col))
+            });
+            for (i, val) in elements.enumerate() {
+                let elem_key = format!("{}.{}", key, i);
+                if let Some(&j) = index.get(&elem_key) {
+                    let (line, col) = positions[j];
+                    return Err(Error {
+                        line,
+                        col,
+                        key: None,
+                        kind: Kind::DuplicateKey(elem_key),
+                    });
+                }
+                result.push((elem_key, val));
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// implements Perl's / Raku's "\v", i.e. vertical white space, the same way
+/// as `cni_format` does
+fn is_vertical_ws(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\u{B}' | '\u{C}' | '\r' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+/// The source text with the byte offsets where lines start, so values can
+/// be found again by their position.
+struct Source<'de> {
+    text: &'de str,
+    lines: Vec<usize>,
+}
+
+impl<'de> Source<'de> {
+    fn new(text: &'de str) -> Self {
+        let mut lines = vec![0];
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            // CRLF is only a single line break
+            if c == '\r' && matches!(chars.peek(), Some((_, '\n'))) {
+                continue;
+            }
+            if is_vertical_ws(c) {
+                lines.push(i + c.len_utf8());
+            }
+        }
+
+        Self { text, lines }
+    }
+
+    /// Borrows the value from the source text if it can be found at the
+    /// given position verbatim. This is not possible e.g. for raw values
+    /// with escaped backticks.
+    fn borrow(&self, val: String, line: usize, col: usize) -> Cow<'de, str> {
+        let found = self
+            .lines
+            .get(line.wrapping_sub(1))
+            .and_then(|&start| {
+                let rest = &self.text[start..];
+                let (offset, _) = rest.char_indices().nth(col.wrapping_sub(1))?;
+                let rest = &rest[offset..];
+                // skip the backtick of raw values
+                let rest = rest.strip_prefix('`').unwrap_or(rest);
+                rest.get(..val.len())
+            })
+            .filter(|&found| found == val);
+
+        match found {
+            Some(found) => Cow::Borrowed(found),
+            None => Cow::Owned(val),
+        }
+    }
+}
+
+/// Parses the text and stores every value with its starting position.
+fn parse<'de>(s: &'de str, options: &DeserializerOptions) -> Result<Entries<'de>> {
+    let source = Source::new(s);
+    let mut parser = CniParser::new_opts(s.chars(), options.cni);
+    let mut data = Entries::default();
 
     while let Some(result) = parser.next() {
         let (key, val) = result?;
         // can unwrap here because the parser must have returned a Ok result
         let (line, col) = parser.last_pos().unwrap();
-        let val = (val, line, col);
-
-        // the format itself allows this, but handle duplicate keys as an error
-        // because it might have unintended consequences
-        match data.entry(key) {
-            Entry::Vacant(e) => e.insert(val),
-            Entry::Occupied(e) => {
-                return Err(Error {
-                    line,
-                    col,
-                    kind: Kind::DuplicateKey(e.remove_entry().0),
-                })
-            }
-        };
+        let val = source.borrow(val, line, col);
+
+        data.insert(key, (val, line, col), options.duplicate_keys)?;
     }
 
+    Ok(data)
+}
+
+fn from_data<'de, T>(data: Entries<'de>, options: &DeserializerOptions) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
     // the whole file is a struct/map so to represent that
     // put the whole tree into a tree with an empty key
-    let mut obj = HashMap::new();
-    obj.insert(String::new(), to_tree(data));
-    T::deserialize(&mut Deserializer::new(obj))
+    let mut de = Deserializer::new(
+        vec![(String::new(), to_tree(data.into_vec()?, "")?)],
+        String::new(),
+        *options,
+    );
+    // errors that are not about a specific value are about the whole file
+    de.advance();
+    T::deserialize(&mut de).map_err(|e| de.locate(e))
 }
 
-fn to_tree(data: HashMap<String, (String, usize, usize)>) -> Tree {
-    let mut map = data
-        .sub_leaves("")
+/// Builds the tree of sections from the flat keys. `path` is the dotted key
+/// of the section that is built, for error messages.
+fn to_tree<'de>(data: Vec<(String, Located<'de>)>, path: &str) -> Result<Tree<'de>> {
+    // group by the first part of the key, keeping the order
+    let mut groups: Vec<(String, Option<Tree>, Vec<_>)> = Vec::new();
+    let mut index = HashMap::new();
+
+    for (key, (val, line, col)) in data {
+        let path = KeyPath::from(key);
+        let (name, rest) = match path.split_first() {
+            Some((name, rest)) if !rest.is_root() => (name.to_string(), Some(rest.to_string())),
+            _ => (path.into_string(), None),
+        };
+        let i = *index.entry(name.clone()).or_insert_with(|| {
+            groups.push((name, None, Vec::new()));
+            groups.len() - 1
+        });
+        match rest {
+            Some(rest) => groups[i].2.push((rest, (val, line, col))),
+            None => groups[i].1 = Some(Tree::Value(val, line, col)),
+        }
+    }
+
+    groups
         .into_iter()
-        .map(|(key, (val, line, col))| (key, Tree::Value(val, line, col)))
-        .collect::<HashMap<_, _>>();
-    map.extend(data.section_leaves("").into_iter().map(|sect| {
-        let tree = to_tree(data.sub_tree(&sect));
-        (sect, tree)
-    }));
-
-    Tree::Map(map)
+        .map(|(name, leaf, section)| {
+            let key = join(path, &name);
+            let tree = match (leaf, section.first()) {
+                (Some(leaf), None) => leaf,
+                (None, Some(_)) => to_tree(section, &key)?,
+                // neither of the two may be dropped silently
+                (Some(leaf), Some((_, (_, line, col)))) => {
+                    let (section_line, section_col) = (*line, *col);
+                    let (line, col) = leaf.pos();
+                    return Err(Error {
+                        line,
+                        col,
+                        key: Some(key),
+                        kind: Kind::ValueAndSection(section_line, section_col),
+                    });
+                }
+                (None, None) => unreachable!("every group has a value or a section"),
+            };
+            Ok((name, tree))
+        })
+        .collect::<Result<_>>()
+        .map(Tree::Map)
 }
@@ -0,0 +1,114 @@
+use super::{from_data, parse};
+use crate::error::{Error, Kind, Result};
+use cni_format::Opts;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::io::Read;
+
+/// How keys that appear more than once are handled.
+///
+/// The CNI format itself specifies that the last value wins, but this might
+/// have unintended consequences, so the default is to handle it as an error.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// A key that appears more than once is an error.
+    #[default]
+    Error,
+    /// The last value for a key is used.
+    LastWins,
+    /// The first value for a key is used.
+    FirstWins,
+    /// All values for a key are collected into a sequence, e.g. for
+    /// deserializing a `Vec`. Each value is split at commas like a single
+    /// value for a sequence, so `a = 1, 2` and `a = 3` result in
+    /// `[1, 2, 3]`. Using an index explicitly as well, e.g. `a.0 = 4`, is
+    /// an error.
+    CollectIntoSeq,
+}
+
+/// How an empty value, e.g. `a =` or `a = #empty`, is deserialized into an
+/// `Option`. A key that does not exist is always `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyValues {
+    /// An empty value is `None`. This is how
+    /// [`cni_format::to_str`](cni_format::to_str) writes empty strings.
+    #[default]
+    None,
+    /// An empty value is `Some`, e.g. `Some("")` for an `Option<String>`.
+    Some,
+}
+
+/// A struct to pass deserialization options.
+///
+/// # Examples
+/// ```
+/// use serde_cni::{DeserializerOptions, DuplicateKeys};
+///
+/// let options = DeserializerOptions {
+///     duplicate_keys: DuplicateKeys::CollectIntoSeq,
+///     ..DeserializerOptions::default()
+/// };
+///
+/// let servers: std::collections::HashMap<String, Vec<String>> = options
+///     .from_str("server = a.example\nserver = b.example\n")
+///     .unwrap();
+///
+/// assert_eq!(servers["server"], vec!["a.example", "b.example"]);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeserializerOptions {
+    /// The options for parsing CNI text. Default: [`Opts::default`]
+    pub cni: Opts,
+    /// How keys that appear more than once are handled.
+    /// Default: [`DuplicateKeys::Error`]
+    pub duplicate_keys: DuplicateKeys,
+    /// Whether values that look like numbers or booleans are presented as
+    /// such if the type that is deserialized does not specify what it
+    /// expects. Default: false
+    ///
+    /// This is the case for `#[serde(flatten)]`, `#[serde(untagged)]` and
+    /// internally tagged enums. With this enabled, the fields of such types
+    /// can be numbers, but a `String` field can not contain a value like `1`.
+    /// With this disabled, it is the other way around, which never changes
+    /// what a value means.
+    pub infer_types: bool,
+    /// How empty values are deserialized into an `Option`.
+    /// Default: [`EmptyValues::None`]
+    pub empty_values: EmptyValues,
+}
+
+impl DeserializerOptions {
+    /// Deserializes a value from CNI format text using these options.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the text is not valid CNI, contains a duplicate
+    /// key that is not allowed or does not match the structure of `T`.
+    pub fn from_str<'de, T>(&self, s: &'de str) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        from_data(parse(s, self)?, self)
+    }
+
+    /// Deserializes a value from CNI format text that is read from the
+    /// reader using these options.
+    ///
+    /// The whole input is read before it is parsed.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the input could not be read or is not valid UTF-8,
+    /// or for the same reasons as [`DeserializerOptions::from_str`].
+    pub fn from_reader<R, T>(&self, mut reader: R) -> Result<T>
+    where
+        R: Read,
+        T: DeserializeOwned,
+    {
+        let mut s = String::new();
+        reader.read_to_string(&mut s).map_err(|e| Error {
+            line: 0,
+            col: 0,
+            key: None,
+            kind: Kind::Io(e.to_string()),
+        })?;
+        from_data(parse(&s, self)?, self)
+    }
+}
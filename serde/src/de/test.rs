@@ -94,3 +94,819 @@ fn map() {
 
     assert_eq!(Ok(map), crate::from_str::<HashMap<String, String>>(cni));
 }
+
+#[test]
+fn env() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        server: Server,
+    }
+
+    let cni = r#"
+        [server]
+        host = localhost
+        port = 80
+    "#;
+
+    std::env::set_var("SERDE_CNI_TEST_SERVER__PORT", "8080");
+
+    assert_eq!(
+        Ok(Test {
+            server: Server {
+                host: "localhost".into(),
+                port: 8080,
+            }
+        }),
+        crate::from_str_with_env::<Test>(cni, &crate::EnvOpts::with_prefix("SERDE_CNI_TEST_"))
+    );
+}
+
+#[test]
+fn seq() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Upstream {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        numbered: Vec<u8>,
+        delimited: Vec<String>,
+        empty: Vec<String>,
+        tuple: (u8, String),
+        upstreams: Vec<Upstream>,
+    }
+
+    let cni = r#"
+        numbered.1 = 2
+        numbered.0 = 1
+        numbered.2 = 3
+        delimited = a, b,c
+        empty = ``
+        tuple.0 = 1
+        tuple.1 = one
+        [upstreams]
+        0.host = a.example
+        0.port = 80
+        1.host = b.example
+        1.port = 8080
+    "#;
+
+    assert_eq!(
+        Ok(Test {
+            numbered: vec![1, 2, 3],
+            delimited: vec!["a".into(), "b".into(), "c".into()],
+            empty: vec![],
+            tuple: (1, "one".into()),
+            upstreams: vec![
+                Upstream {
+                    host: "a.example".into(),
+                    port: 80,
+                },
+                Upstream {
+                    host: "b.example".into(),
+                    port: 8080,
+                },
+            ],
+        }),
+        crate::from_str::<Test>(cni)
+    );
+}
+
+#[test]
+fn seq_errors() {
+    use crate::error::{Error, Kind};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        list: Vec<u8>,
+    }
+
+    assert_eq!(
+        Err(Error {
+            line: 2,
+            col: 10,
+            key: Some("list".into()),
+            kind: Kind::SeqGap(1),
+        }),
+        crate::from_str::<Test>("list.0 = 1\nlist.2 = 2\n")
+    );
+    assert_eq!(
+        Err(Error {
+            line: 1,
+            col: 11,
+            key: Some("list".into()),
+            kind: Kind::SeqIndex("01".into()),
+        }),
+        crate::from_str::<Test>("list.01 = 1\n")
+    );
+}
+
+#[test]
+fn enum_() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Mode {
+        Fast,
+        Slow,
+        Limited(u32),
+        Range(u32, u32),
+        Custom { speed: u8, name: String },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        a: Mode,
+        b: Mode,
+        c: Mode,
+        d: Mode,
+        e: Mode,
+        f: Vec<Mode>,
+    }
+
+    let cni = r#"
+        a = Fast
+        b.Limited = 10
+        c.Range.0 = 1
+        c.Range.1 = 5
+        [d.Custom]
+        speed = 3
+        name = turbo
+        [e]
+        Slow = #empty
+        [f]
+        0 = Slow
+        1.Limited = 2
+    "#;
+
+    assert_eq!(
+        Ok(Test {
+            a: Mode::Fast,
+            b: Mode::Limited(10),
+            c: Mode::Range(1, 5),
+            d: Mode::Custom {
+                speed: 3,
+                name: "turbo".into(),
+            },
+            e: Mode::Slow,
+            f: vec![Mode::Slow, Mode::Limited(2)],
+        }),
+        crate::from_str::<Test>(cni)
+    );
+
+    use crate::error::{Error, Kind};
+    assert_eq!(
+        Err(Error {
+            line: 1,
+            col: 13,
+            key: Some("a".into()),
+            kind: Kind::Enum,
+        }),
+        crate::from_str::<Test>("a.Limited = 1\na.Fast = #empty\n").map(|_| ())
+    );
+}
+
+#[test]
+fn enum_tagged() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(tag = "type")]
+    enum Internal {
+        File { path: String },
+        Stdout,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(tag = "type", content = "value")]
+    enum Adjacent {
+        Name(String),
+        Anonymous,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        log: Internal,
+        out: Internal,
+        user: Adjacent,
+    }
+
+    let cni = r#"
+        log.type = File
+        log.path = /var/log/app.log
+        out.type = Stdout
+        user.type = Name
+        user.value = root
+    "#;
+
+    assert_eq!(
+        Ok(Test {
+            log: Internal::File {
+                path: "/var/log/app.log".into()
+            },
+            out: Internal::Stdout,
+            user: Adjacent::Name("root".into()),
+        }),
+        crate::from_str::<Test>(cni)
+    );
+}
+
+#[test]
+fn order() {
+    #[derive(Debug, PartialEq)]
+    struct Keys(Vec<String>);
+
+    impl<'de> Deserialize<'de> for Keys {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct KeysVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for KeysVisitor {
+                type Value = Keys;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "a map")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Keys, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    let mut keys = Vec::new();
+                    while let Some(key) = map.next_key()? {
+                        map.next_value::<serde::de::IgnoredAny>()?;
+                        keys.push(key);
+                    }
+                    Ok(Keys(keys))
+                }
+            }
+
+            deserializer.deserialize_map(KeysVisitor)
+        }
+    }
+
+    assert_eq!(
+        Ok(Keys(vec!["z".into(), "a".into(), "m".into(), "b".into()])),
+        crate::from_str::<Keys>("z = 1\na.x = 2\nm = 3\na.y = 4\nb = 5\n")
+    );
+}
+
+#[test]
+fn error_location() {
+    use crate::error::{Error, Kind};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        server: Server,
+    }
+
+    let err = crate::from_str::<Test>("[server]\nhost = localhost\nport = http\n").unwrap_err();
+    assert_eq!((err.line, err.col), (3, 8));
+    assert_eq!(err.key.as_deref(), Some("server.port"));
+    assert_eq!(
+        err.to_string(),
+        "line 3:8: server.port: malformed integer: invalid digit found in string"
+    );
+
+    assert_eq!(
+        Err(Error {
+            line: 2,
+            col: 8,
+            key: Some("server.port".into()),
+            kind: Kind::MissingField("port"),
+        }),
+        crate::from_str::<Test>("[server]\nhost = localhost\n")
+    );
+
+    let err =
+        crate::from_str::<Test>("[server]\nhost = localhost\nport = 80\nname = x\n").unwrap_err();
+    assert_eq!((err.line, err.col), (4, 8));
+    assert_eq!(err.key.as_deref(), Some("server.name"));
+
+    let err = crate::from_str::<Test>("[server]\nhost = localhost\nport = 80\nhost.name = x\n")
+        .unwrap_err();
+    assert_eq!(err.key.as_deref(), Some("server.host"));
+}
+
+#[test]
+fn entry_points() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        a: u8,
+        b: String,
+    }
+
+    let expected = Ok(Test {
+        a: 1,
+        b: "two".into(),
+    });
+
+    let ini = crate::Opts {
+        ini: true,
+        more_keys: false,
+    };
+    assert_eq!(
+        expected,
+        crate::from_str_opts::<Test>("a = 1 ; one\nb = two\n", ini)
+    );
+    assert_eq!(
+        expected,
+        crate::from_reader_opts::<_, Test>("a = 1 ; one\nb = two\n".as_bytes(), ini)
+    );
+    assert_eq!(
+        expected,
+        crate::from_reader::<_, Test>(&b"a = 1\nb = two\n"[..])
+    );
+
+    let mut map = cni_format::from_str("a = 1\nb = one\n").unwrap();
+    map.extend(cni_format::from_str("b = two\n").unwrap());
+    assert_eq!(expected, crate::from_map::<_, _, _, Test>(map));
+
+    let err = crate::from_reader::<_, Test>(&b"a = \xff\n"[..]).unwrap_err();
+    assert!(matches!(err.kind, crate::error::Kind::Io(_)));
+}
+
+#[test]
+fn duplicate_keys() {
+    use crate::{DeserializerOptions, DuplicateKeys};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        a: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct List {
+        a: Vec<String>,
+    }
+
+    let cni = "a = first\nb = other\na = second\n";
+    let options = |duplicate_keys| DeserializerOptions {
+        duplicate_keys,
+        ..DeserializerOptions::default()
+    };
+
+    let err = options(DuplicateKeys::Error)
+        .from_str::<Test>(cni)
+        .unwrap_err();
+    assert_eq!((err.line, err.col), (3, 5));
+    assert_eq!(err.kind, crate::error::Kind::DuplicateKey("a".into()));
+
+    assert_eq!(
+        Ok(Test { a: "second".into() }),
+        options(DuplicateKeys::LastWins).from_str(cni)
+    );
+    assert_eq!(
+        Ok(Test { a: "first".into() }),
+        options(DuplicateKeys::FirstWins).from_str(cni)
+    );
+    assert_eq!(
+        Ok(List {
+            a: vec!["first".into(), "second".into()]
+        }),
+        options(DuplicateKeys::CollectIntoSeq).from_str(cni)
+    );
+
+    // every value is split at commas, no matter how often the key appears
+    let expected = Ok(List {
+        a: vec!["x".into(), "y".into(), "z".into()],
+    });
+    assert_eq!(
+        expected,
+        options(DuplicateKeys::CollectIntoSeq).from_str("a = x, y, z\n")
+    );
+    assert_eq!(
+        expected,
+        options(DuplicateKeys::CollectIntoSeq).from_str("a = x, y\na = z\n")
+    );
+
+    // the generated indices may not be used explicitly as well
+    let err = options(DuplicateKeys::CollectIntoSeq)
+        .from_str::<List>("a = x\na = y\na.0 = z\n")
+        .unwrap_err();
+    assert_eq!(err.kind, crate::error::Kind::DuplicateKey("a.0".into()));
+    assert_eq!((err.line, err.col), (3, 7));
+}
+
+#[test]
+fn flatten() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Common {
+        name: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        #[serde(flatten)]
+        common: Common,
+        enabled: bool,
+        #[serde(flatten)]
+        rest: HashMap<String, String>,
+    }
+
+    let cni = r#"
+        name = web
+        port = 8080
+        enabled = true
+        extra = value
+    "#;
+
+    // flattened numbers need type inference
+    let options = crate::DeserializerOptions {
+        infer_types: true,
+        ..crate::DeserializerOptions::default()
+    };
+    assert_eq!(
+        Ok(Test {
+            common: Common {
+                name: "web".into(),
+                port: 8080,
+            },
+            enabled: true,
+            rest: [("extra".to_string(), "value".to_string())].into(),
+        }),
+        options.from_str::<Test>(cni)
+    );
+
+    // which then makes flattened strings fail. The values are buffered, so
+    // the error is about the section they are in.
+    let err = options
+        .from_str::<Test>("name = web\nport = 80\nenabled = true\nextra = 5\n")
+        .unwrap_err();
+    assert_eq!(err.key, None);
+    assert_eq!((err.line, err.col), (1, 8));
+
+    let cni = "[a]\nname = x\nport = 5\nenabled = true\nextra = x\n\
+               [b]\nname = y\nport = 5\nenabled = true\nextra = 5\n";
+    let err = options.from_str::<HashMap<String, Test>>(cni).unwrap_err();
+    assert_eq!(err.key.as_deref(), Some("b"));
+    assert_eq!((err.line, err.col), (7, 8));
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Strings {
+        name: String,
+        #[serde(flatten)]
+        rest: HashMap<String, String>,
+    }
+
+    // without inference, strings are kept as they are
+    assert_eq!(
+        Ok(Strings {
+            name: "web".into(),
+            rest: [
+                ("extra".to_string(), "5".to_string()),
+                ("version".to_string(), "1.10".to_string()),
+            ]
+            .into(),
+        }),
+        crate::from_str::<Strings>("name = web\nextra = 5\nversion = 1.10\n")
+    );
+
+    let err = crate::from_str::<Test>("name = web\nport = x\nenabled = true\n").unwrap_err();
+    assert_eq!(err.key, None);
+    assert_eq!((err.line, err.col), (1, 8));
+}
+
+#[test]
+fn internally_tagged() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(tag = "type")]
+    enum Output {
+        File { path: String },
+        Socket { port: u16 },
+    }
+
+    assert_eq!(
+        Ok(Output::File { path: "123".into() }),
+        crate::from_str::<Output>("type = File\npath = 123\n")
+    );
+
+    let err = crate::from_str::<Output>("type = Socket\nport = x\n").unwrap_err();
+    assert_eq!(err.key, None);
+    assert_eq!((err.line, err.col), (1, 8));
+
+    #[derive(Debug, Deserialize)]
+    struct Outputs {
+        #[allow(dead_code)]
+        log: Output,
+    }
+    let err = crate::from_str::<Outputs>("[log]\ntype = Socket\nport = x\n").unwrap_err();
+    assert_eq!(err.key.as_deref(), Some("log"));
+    assert_eq!((err.line, err.col), (2, 8));
+
+    let options = crate::DeserializerOptions {
+        infer_types: true,
+        ..crate::DeserializerOptions::default()
+    };
+    assert_eq!(
+        Ok(Output::Socket { port: 80 }),
+        options.from_str::<Output>("type = Socket\nport = 80\n")
+    );
+}
+
+#[test]
+fn untagged() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(untagged)]
+    enum Limit {
+        Count(u32),
+        Ratio(f64),
+        Enabled(bool),
+        Named(String),
+        Range { min: i32, max: i32 },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        a: Limit,
+        b: Limit,
+        c: Limit,
+        d: Limit,
+        e: Limit,
+    }
+
+    let cni = r#"
+        a = 10
+        b = 0.5
+        c = false
+        d = unlimited
+        e.min = -1
+        e.max = 1
+    "#;
+
+    let options = crate::DeserializerOptions {
+        infer_types: true,
+        ..crate::DeserializerOptions::default()
+    };
+    assert_eq!(
+        Ok(Test {
+            a: Limit::Count(10),
+            b: Limit::Ratio(0.5),
+            c: Limit::Enabled(false),
+            d: Limit::Named("unlimited".into()),
+            e: Limit::Range { min: -1, max: 1 },
+        }),
+        options.from_str::<Test>(cni)
+    );
+
+    // without type inference, everything is a string
+    assert_eq!(
+        Ok([("a".to_string(), Limit::Named("10".into()))].into()),
+        crate::from_str::<HashMap<String, Limit>>("a = 10")
+    );
+}
+
+#[test]
+fn borrowed() {
+    use std::borrow::Cow;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test<'a> {
+        a: &'a str,
+        #[serde(borrow)]
+        b: Cow<'a, str>,
+        #[serde(borrow)]
+        c: Cow<'a, str>,
+        d: &'a str,
+    }
+
+    let cni = "[section]\r\na = plain\nb = `raw value`\nc = `escaped `` tick`\nd = µ ü ß\n";
+
+    let test: Test = crate::from_str(&cni[11..]).unwrap();
+    assert_eq!(test.a, "plain");
+    assert!(matches!(test.b, Cow::Borrowed("raw value")));
+    // the escaped backtick can not be borrowed
+    assert!(matches!(test.c, Cow::Owned(ref c) if c == "escaped ` tick"));
+    assert_eq!(test.d, "µ ü ß");
+
+    // positions after a CRLF line break are found as well
+    let test: HashMap<String, Test> = crate::from_str(cni).unwrap();
+    assert_eq!(test["section"].a, "plain");
+    assert!(matches!(test["section"].b, Cow::Borrowed("raw value")));
+}
+
+#[test]
+fn value_and_section() {
+    let err = crate::from_str::<HashMap<String, HashMap<String, String>>>(
+        "[server]\nhost = a\nhost.port = 1\n",
+    )
+    .unwrap_err();
+    assert_eq!((err.line, err.col), (2, 8));
+    assert_eq!(err.key.as_deref(), Some("server.host"));
+    assert_eq!(err.kind, crate::error::Kind::ValueAndSection(3, 13));
+    assert_eq!(
+        err.to_string(),
+        "line 2:8: server.host: key has a value and is also a section starting at line 3:13"
+    );
+
+    // also if the section comes first
+    let err = crate::from_str::<HashMap<String, String>>("a.b = 1\na = 2\n").unwrap_err();
+    assert_eq!((err.line, err.col), (2, 5));
+    assert_eq!(err.kind, crate::error::Kind::ValueAndSection(1, 7));
+}
+
+#[test]
+fn option() {
+    use crate::{DeserializerOptions, EmptyValues};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Sub {
+        port: u16,
+        host: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        a: Option<String>,
+        b: Option<String>,
+        c: Option<u32>,
+        d: Option<Vec<String>>,
+        server: Option<Sub>,
+        backup: Option<Sub>,
+    }
+
+    // serialize the flat map the same way as a user of cni_format would
+    let cni = cni_format::to_str(vec![
+        ("a", ""),
+        ("c", "7"),
+        ("d", "x, y"),
+        ("server.port", "80"),
+        ("server.host", ""),
+    ])
+    .unwrap();
+    assert!(cni.contains("a = #empty\n"));
+
+    assert_eq!(
+        Ok(Test {
+            a: None,
+            b: None,
+            c: Some(7),
+            d: Some(vec!["x".into(), "y".into()]),
+            server: Some(Sub {
+                port: 80,
+                host: None,
+            }),
+            backup: None,
+        }),
+        crate::from_str::<Test>(&cni)
+    );
+
+    let options = DeserializerOptions {
+        empty_values: EmptyValues::Some,
+        ..DeserializerOptions::default()
+    };
+    assert_eq!(
+        Ok(Test {
+            a: Some(String::new()),
+            b: None,
+            c: Some(7),
+            d: Some(vec!["x".into(), "y".into()]),
+            server: Some(Sub {
+                port: 80,
+                host: Some(String::new()),
+            }),
+            backup: None,
+        }),
+        options.from_str::<Test>(&cni)
+    );
+
+    // the value is still checked if it is present
+    let err = crate::from_str::<Test>("c = x\n").unwrap_err();
+    assert_eq!(err.key.as_deref(), Some("c"));
+    assert_eq!((err.line, err.col), (1, 5));
+}
+
+#[test]
+fn types() {
+    use std::net::{IpAddr, SocketAddr};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        #[serde(with = "crate::types::duration")]
+        timeout: Duration,
+        #[serde(with = "crate::types::size")]
+        cache: u64,
+        listen: SocketAddr,
+        peer: IpAddr,
+        data: PathBuf,
+    }
+
+    let cni = r#"
+        timeout = 1h 30m
+        cache = 512KiB
+        listen = [::1]:8080
+        peer = 192.0.2.1
+        data = /var/lib/app
+    "#;
+
+    assert_eq!(
+        Ok(Test {
+            timeout: Duration::from_secs(90 * 60),
+            cache: 512 * 1024,
+            listen: "[::1]:8080".parse().unwrap(),
+            peer: "192.0.2.1".parse().unwrap(),
+            data: "/var/lib/app".into(),
+        }),
+        crate::from_str::<Test>(cni)
+    );
+
+    let err = crate::from_str::<Test>(&cni.replace("1h 30m", "soon")).unwrap_err();
+    assert_eq!(err.key.as_deref(), Some("timeout"));
+    assert_eq!((err.line, err.col), (2, 19));
+    assert_eq!(err.to_string(), "line 2:19: timeout: malformed duration");
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Limits {
+        #[serde(with = "crate::types::size")]
+        cache: u64,
+        #[serde(with = "crate::types::duration")]
+        timeout: Duration,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Flattened {
+        name: String,
+        #[serde(flatten)]
+        limits: Limits,
+    }
+
+    // inferred numbers are converted like strings
+    let options = crate::DeserializerOptions {
+        infer_types: true,
+        ..crate::DeserializerOptions::default()
+    };
+    assert_eq!(
+        Ok(Flattened {
+            name: "web".into(),
+            limits: Limits {
+                cache: 4096,
+                timeout: Duration::from_secs(90),
+            },
+        }),
+        options.from_str::<Flattened>("name = web\ncache = 4096\ntimeout = 1m30s\n")
+    );
+
+    let err = options
+        .from_str::<Flattened>("name = web\ncache = 4096\ntimeout = 90\n")
+        .unwrap_err();
+    assert_eq!(err.key, None);
+    assert_eq!((err.line, err.col), (1, 8));
+}
+
+#[test]
+fn value() {
+    use crate::{Table, Value};
+
+    let cni = r#"
+        name = web
+        [server]
+        port = 8080
+        tls.enabled = yes
+    "#;
+
+    let table = crate::from_str::<Table>(cni).unwrap();
+    assert_eq!(table["name"], Value::from("web"));
+    assert_eq!(table["server"]["port"].as_str(), Some("8080"));
+    assert_eq!(table["server.tls.enabled"].as_str(), Some("yes"));
+    assert_eq!(table, cni.parse().unwrap());
+
+    let err = crate::from_str::<Table>("a = 1\na.b = 2\n").unwrap_err();
+    assert_eq!(err.kind, crate::error::Kind::ValueAndSection(2, 7));
+
+    // values are kept as they are written, even with type inference
+    let cni = "version = 1.10\ncount = 007\nx = +5\ny = 1e3\nz.w = true\n";
+    let options = crate::DeserializerOptions {
+        infer_types: true,
+        ..crate::DeserializerOptions::default()
+    };
+    for table in [
+        crate::from_str::<Table>(cni).unwrap(),
+        options.from_str::<Table>(cni).unwrap(),
+    ] {
+        assert_eq!(table["version"].as_str(), Some("1.10"));
+        assert_eq!(table["count"].as_str(), Some("007"));
+        assert_eq!(table["x"].as_str(), Some("+5"));
+        assert_eq!(table["y"].as_str(), Some("1e3"));
+        assert_eq!(table["z.w"].as_str(), Some("true"));
+    }
+    assert_eq!(
+        options.from_str::<HashMap<String, Value>>(cni).unwrap()["version"],
+        Value::from("1.10")
+    );
+}
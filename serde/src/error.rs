@@ -6,12 +6,17 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct Error {
     pub line: usize,
     pub col: usize,
+    /// The dotted key of the value the error is about, if known.
+    pub key: Option<String>,
     pub kind: Kind,
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "line {}:{}: {}", self.line, self.col, self.kind)
+        match &self.key {
+            Some(key) => write!(f, "line {}:{}: {}: {}", self.line, self.col, key, self.kind),
+            None => write!(f, "line {}:{}: {}", self.line, self.col, self.kind),
+        }
     }
 }
 
@@ -20,6 +25,7 @@ impl From<cni_format::error::Error> for Error {
         Self {
             line: err.line,
             col: err.col,
+            key: None,
             kind: err.kind.into(),
         }
     }
@@ -54,6 +60,22 @@ pub enum Kind {
     DuplicateKey(String),
     /// Error in the data representation: no more value(s)
     ExpectedValues,
+    /// Error in the data representation: a key in a sequence is not an index
+    SeqIndex(String),
+    /// Error in the data representation: the element with this index is
+    /// missing from a sequence
+    SeqGap(usize),
+    /// Error in the data representation: a field is missing
+    MissingField(&'static str),
+    /// Error in the data representation: an enum section does not contain
+    /// exactly one variant
+    Enum,
+    /// Error in the data representation: a key has a value and is also a
+    /// section, contains the position of the first value in the section
+    ValueAndSection(usize, usize),
+
+    /// The input could not be read, contains the message of the I/O error.
+    Io(String),
 
     /// custom error message
     Custom(String),
@@ -75,6 +97,17 @@ impl std::fmt::Display for Kind {
             Self::Char => write!(f, "malformed character value"),
             Self::DuplicateKey(key) => write!(f, "key '{}' appears multiple times", key),
             Self::ExpectedValues => write!(f, "expected more values, but this is the last one"),
+            Self::SeqIndex(key) => write!(f, "'{}' is not a valid sequence index", key),
+            Self::SeqGap(i) => write!(f, "sequence element {} is missing", i),
+            Self::MissingField(_) => write!(f, "missing field"),
+            Self::Enum => write!(f, "expected exactly one enum variant"),
+            Self::ValueAndSection(line, col) => write!(
+                f,
+                "key has a value and is also a section starting at line {}:{}",
+                line, col
+            ),
+
+            Self::Io(msg) => write!(f, "could not read input: {}", msg),
 
             Self::Custom(s) => write!(f, "{}", s),
         }
@@ -100,9 +133,19 @@ impl serde::de::Error for Error {
         Error {
             line: 0,
             col: 0,
+            key: None,
             kind: Kind::Custom(msg.to_string()),
         }
     }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error {
+            line: 0,
+            col: 0,
+            key: None,
+            kind: Kind::MissingField(field),
+        }
+    }
 }
 
 impl std::error::Error for Error {}
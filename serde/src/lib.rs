@@ -1,7 +1,56 @@
+//! This is a [serde] implementation for the
+//! [CNI configuration format](https://github.com/libuconf/cni/).
+//!
+//! # Data representation
+//! Since CNI only knows keys and string values, other data types are
+//! represented like this:
+//!
+//! * Structs and maps use their field names or keys as keys, nested
+//!   structs and maps are sections.
+//! * Sequences and tuples use the indices as keys, e.g. `list.0`, `list.1`.
+//!   The indices have to start at 0 and may not have gaps. Alternatively, a
+//!   single value is split at commas, e.g. `list = a, b`.
+//! * Unit enum variants are just the name of the variant, e.g.
+//!   `mode = Fast`. Other variants are a section named after the variant
+//!   that contains the data, e.g. `mode.Limited = 10` or
+//!   `mode.Custom.speed = 3`.
+//! * `None` is a key that does not exist, including whole sections for
+//!   optional structs. An empty value like `a = #empty` is also `None`
+//!   unless configured otherwise with [`EmptyValues`].
+//!
+//! CNI allows a key to have a value and also be a section, e.g. `a = 1` and
+//! `a.b = 2`. This can not be represented in the data model, so it is an
+//! error instead of silently dropping one of the two.
+//!
+//! Maps and structs are visited in the order in which the keys first appear
+//! in the source.
+//!
+//! Where the type does not say what it expects, e.g. with
+//! `#[serde(flatten)]` or `#[serde(untagged)]`, values are presented as
+//! strings unless [`DeserializerOptions::infer_types`] is enabled. Strings are borrowed from the
+//! source if possible, so `&str` fields work for values that need no
+//! unescaping.
+//!
+//! To inspect data without defining a struct, deserialize it into a
+//! [`Table`]. Use [`Table`]'s `FromStr` implementation instead if keys may
+//! have a value and also be a section.
+//!
+//! # Errors
+//! Errors contain the line and column of the value they are about and, if
+//! possible, its dotted key, e.g. `line 3:8: server.port: malformed integer`.
+//! Values that serde buffers, e.g. for `#[serde(flatten)]`, are only
+//! checked after the whole section was read, so their errors point to the
+//! section instead.
+
 mod de;
 mod error;
 //mod ser;
+pub mod types;
 
-pub use de::{from_str, Deserializer};
+pub use cni_format::{EnvOpts, Opts, Table, Value};
+pub use de::{
+    from_map, from_reader, from_reader_opts, from_str, from_str_opts, from_str_with_env,
+    Deserializer, DeserializerOptions, DuplicateKeys, EmptyValues,
+};
 pub use error::{Error, Result};
 //pub use ser::{to_string, Serializer};
@@ -0,0 +1,164 @@
+//! Helpers for common value types, to be used with `#[serde(with = "...")]`.
+//!
+//! They use the conversions from [`cni_format::convert`], so values are
+//! written the same way as for [`CniTypedExt`](cni_format::CniTypedExt).
+//! If a value can not be converted, the error contains its position as
+//! usual.
+//!
+//! Types that already have a string representation in serde, like
+//! [`SocketAddr`](std::net::SocketAddr), [`IpAddr`](std::net::IpAddr) and
+//! [`PathBuf`](std::path::PathBuf), need no helper, e.g. `[::1]:8080` can
+//! be deserialized into a `SocketAddr` directly.
+//!
+//! # Examples
+//! ```
+//! use serde::Deserialize;
+//! use std::net::SocketAddr;
+//! use std::time::Duration;
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     listen: SocketAddr,
+//!     #[serde(with = "serde_cni::types::duration")]
+//!     timeout: Duration,
+//!     #[serde(with = "serde_cni::types::size")]
+//!     max_body: u64,
+//! }
+//!
+//! let config: Config = serde_cni::from_str(r"
+//! listen = [::1]:8080
+//! timeout = 1m30s
+//! max_body = 512KiB
+//! ").unwrap();
+//!
+//! assert_eq!(config.listen.port(), 8080);
+//! assert_eq!(config.timeout, Duration::from_secs(90));
+//! assert_eq!(config.max_body, 512 * 1024);
+//! ```
+
+use cni_format::error::ValueKind;
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Visits a string and converts it with the given function. Numbers are
+/// converted from their string representation.
+struct ConvertVisitor<T> {
+    expecting: &'static str,
+    convert: fn(&str) -> Result<T, ValueKind>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T> Visitor<'de> for ConvertVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        (self.convert)(v).map_err(E::custom)
+    }
+
+    // values whose type was inferred, e.g. in a `#[serde(flatten)]` struct.
+    // The errors name the value, since they may be about a whole section.
+    fn visit_u64<E>(self, v: u64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        (self.convert)(&v.to_string()).map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        (self.convert)(&v.to_string()).map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        (self.convert)(&v.to_string()).map_err(|_| E::invalid_value(Unexpected::Float(v), &self))
+    }
+}
+
+fn deserialize_with<'de, D, T>(
+    deserializer: D,
+    expecting: &'static str,
+    convert: fn(&str) -> Result<T, ValueKind>,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    // not `deserialize_str`, buffered values whose type was inferred would
+    // be rejected before reaching the visitor
+    deserializer.deserialize_any(ConvertVisitor {
+        expecting,
+        convert,
+        marker: PhantomData,
+    })
+}
+
+/// (De)serializes a [`Duration`](std::time::Duration) like `30s` or `1h30m`,
+/// see [`parse_duration`](cni_format::convert::parse_duration).
+pub mod duration {
+    use cni_format::convert::{format_duration, parse_duration};
+    use serde::{Deserializer, Serializer};
+    use std::time::Duration;
+
+    /// Serializes a duration using the largest units possible.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the serializer fails.
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_duration(*duration))
+    }
+
+    /// Deserializes a duration.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value is not a duration.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_with(deserializer, "a duration", parse_duration)
+    }
+}
+
+/// (De)serializes a size in bytes as a `u64` like `4096` or `512KiB`, see
+/// [`parse_size`](cni_format::convert::parse_size).
+pub mod size {
+    use cni_format::convert::{format_size, parse_size};
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes a size using the largest binary unit possible.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the serializer fails.
+    pub fn serialize<S>(size: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_size(*size))
+    }
+
+    /// Deserializes a size.
+    ///
+    /// # Errors
+    /// Returns an `Err` if the value is not a size.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_with(deserializer, "a size", parse_size)
+    }
+}
@@ -0,0 +1,154 @@
+use crate::diff::pair;
+use crate::input;
+use cni_format::{CniExt, KeyPath, Opts};
+use std::collections::BTreeSet;
+
+/// The result of comparing the keys of several files.
+#[derive(Default)]
+struct Report<'f> {
+    /// Keys that are missing from some files, with the files that are
+    /// missing them and the files that have them.
+    missing: Vec<(String, Vec<&'f str>, Vec<&'f str>)>,
+    /// Keys that have the same value in all files.
+    identical: Vec<(String, String)>,
+    /// Sections that only exist in one file.
+    single_file_sections: Vec<(String, &'f str)>,
+}
+
+/// Compares the keys of the files. Returns whether the files are consistent,
+/// i.e. no key is missing in any file and no section only exists in one file.
+pub fn check(files: &[&str], json: bool, opts: Opts) -> Result<bool, String> {
+    let maps = files
+        .iter()
+        .map(|file| input::load(file, opts))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut keys = maps
+        .iter()
+        .flat_map(|map| map.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    keys.sort_by(|a, b| {
+        let (a, b) = (KeyPath::from(a), KeyPath::from(b));
+        a.segments().cmp(b.segments())
+    });
+
+    let mut report = Report::default();
+    for key in keys {
+        let (present, missing): (Vec<_>, Vec<_>) = files
+            .iter()
+            .zip(&maps)
+            .partition(|(_, map)| map.contains_key(&key));
+
+        if !missing.is_empty() {
+            report.missing.push((
+                key,
+                missing.into_iter().map(|(file, _)| *file).collect(),
+                present.into_iter().map(|(file, _)| *file).collect(),
+            ));
+        } else if let Some(((_, first), rest)) = present.split_first() {
+            let value = &first[&key].value;
+            if !rest.is_empty() && rest.iter().all(|(_, map)| &map[&key].value == value) {
+                report.identical.push((key, value.clone()));
+            }
+        }
+    }
+
+    // only report the topmost section, its subsections are also only in
+    // that one file
+    let sections = maps
+        .iter()
+        .map(|map| map.iter().section_tree(KeyPath::root()))
+        .collect::<Vec<_>>();
+    let mut single = Vec::new();
+    for (file, own) in files.iter().zip(&sections) {
+        for section in own {
+            let count = sections.iter().filter(|s| s.contains(section)).count();
+            if count == 1 {
+                single.push((section.clone(), *file));
+            }
+        }
+    }
+    single.sort_by(|(a, _), (b, _)| {
+        let (a, b) = (KeyPath::from(a), KeyPath::from(b));
+        a.segments().cmp(b.segments())
+    });
+    for (section, file) in single {
+        let reported = report
+            .single_file_sections
+            .iter()
+            .any(|(other, _)| KeyPath::from(&section).strip_prefix(other).is_some());
+        if !reported {
+            report.single_file_sections.push((section, file));
+        }
+    }
+
+    if json {
+        print_json(&report, files);
+    } else {
+        print_human(&report, opts);
+    }
+
+    Ok(report.missing.is_empty() && report.single_file_sections.is_empty())
+}
+
+fn print_human(report: &Report, opts: Opts) {
+    if !report.missing.is_empty() {
+        println!("keys missing from some files:");
+        for (key, missing, present) in &report.missing {
+            println!(
+                "  {}: missing in {}, set in {}",
+                key,
+                missing.join(", "),
+                present.join(", ")
+            );
+        }
+    }
+    if !report.single_file_sections.is_empty() {
+        println!("sections that only exist in one file:");
+        for (section, file) in &report.single_file_sections {
+            println!("  [{}] in {}", section, file);
+        }
+    }
+    if !report.identical.is_empty() {
+        println!("keys with the same value in all files:");
+        for (key, value) in &report.identical {
+            println!("  {}", pair(key, value, opts));
+        }
+    }
+}
+
+fn print_json(report: &Report, files: &[&str]) {
+    let missing = report
+        .missing
+        .iter()
+        .map(|(key, missing, present)| {
+            serde_json::json!({
+                "key": key,
+                "missing_in": missing,
+                "present_in": present,
+            })
+        })
+        .collect::<Vec<_>>();
+    let identical = report
+        .identical
+        .iter()
+        .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+        .collect::<Vec<_>>();
+    let sections = report
+        .single_file_sections
+        .iter()
+        .map(|(section, file)| serde_json::json!({ "section": section, "file": file }))
+        .collect::<Vec<_>>();
+
+    println!(
+        "{}",
+        serde_json::json!({
+            "files": files,
+            "missing": missing,
+            "identical": identical,
+            "single_file_sections": sections,
+        })
+    );
+}
@@ -0,0 +1,193 @@
+use crate::input::{self, Entry};
+use cni_format::{Change, ChangeKind, Glob, KeyPath, Opts};
+use std::collections::HashMap;
+
+pub enum Output {
+    /// Changes grouped by section with the positions they were read from.
+    Human,
+    /// Removed and added lines in CNI syntax, like `diff -u`.
+    Unified,
+    /// A JSON object with a list of changes.
+    Json,
+}
+
+/// Compares two CNI files and prints the differences. Returns whether there
+/// were any differences.
+pub fn diff(
+    old_file: &str,
+    new_file: &str,
+    ignore: &[Glob],
+    output: Output,
+    opts: Opts,
+) -> Result<bool, String> {
+    let old = input::load(old_file, opts)?;
+    let new = input::load(new_file, opts)?;
+
+    let changes = cni_format::diff(&old, &new)
+        .into_iter()
+        .filter(|change| !ignore.iter().any(|glob| glob.is_match(change.key)))
+        .collect::<Vec<_>>();
+
+    match output {
+        Output::Human => print_human(&changes, old_file, new_file, opts),
+        Output::Unified => print_unified(&changes, old_file, new_file, opts),
+        Output::Json => print_json(&changes, old_file, new_file),
+    }
+
+    Ok(!changes.is_empty())
+}
+
+/// Formats a key/value pair as CNI, which takes care of values that have to
+/// be written as raw values. A key the serializer rejects is written as it
+/// was read.
+pub fn pair(key: &str, value: &str, opts: Opts) -> String {
+    to_cni(key, value, opts).unwrap_or_else(|_| format!("{} = {}", key, self::value(value, opts)))
+}
+
+/// Formats only the value of a pair, like it would be written in CNI.
+pub fn value(value: &str, opts: Opts) -> String {
+    // `k` is a valid key with any options
+    to_cni("k", value, opts)
+        .map_or_else(|_| value.to_string(), |cni| cni["k = ".len()..].to_string())
+}
+
+fn to_cni(key: &str, value: &str, opts: Opts) -> Result<String, cni_format::error::KeyError> {
+    let mut map = HashMap::new();
+    map.insert(key, value);
+    let mut cni = cni_format::to_str_with(
+        map,
+        &cni_format::SerializerOptions {
+            headers: cni_format::Headers::Never,
+            cni: opts,
+            ..cni_format::SerializerOptions::default()
+        },
+    )?;
+    cni.truncate(cni.trim_end().len());
+    Ok(cni)
+}
+
+fn print_human(changes: &[Change<Entry>], old_file: &str, new_file: &str, opts: Opts) {
+    let mut section = "";
+    for change in changes {
+        if change.section() != section {
+            section = change.section();
+            println!("[{}]", section);
+        }
+        let name = KeyPath::from(change.key)
+            .name()
+            .unwrap_or_default()
+            .to_string();
+
+        match (change.old, change.new) {
+            (Some(old), None) => println!(
+                "- {} ({}:{}:{})",
+                pair(&name, &old.value, opts),
+                old_file,
+                old.line,
+                old.col
+            ),
+            (None, Some(new)) => println!(
+                "+ {} ({}:{}:{})",
+                pair(&name, &new.value, opts),
+                new_file,
+                new.line,
+                new.col
+            ),
+            (Some(old), Some(new)) => println!(
+                "~ {} -> {} ({}:{}:{} -> {}:{}:{})",
+                pair(&name, &old.value, opts),
+                value(&new.value, opts),
+                old_file,
+                old.line,
+                old.col,
+                new_file,
+                new.line,
+                new.col
+            ),
+            (None, None) => unreachable!("change without values"),
+        }
+    }
+}
+
+fn print_unified(changes: &[Change<Entry>], old_file: &str, new_file: &str, opts: Opts) {
+    if changes.is_empty() {
+        return;
+    }
+
+    println!("--- {}", old_file);
+    println!("+++ {}", new_file);
+
+    let mut section = "";
+    for change in changes {
+        if change.section() != section {
+            section = change.section();
+            // section headers are context lines
+            println!(" [{}]", section);
+        }
+        let name = KeyPath::from(change.key)
+            .name()
+            .unwrap_or_default()
+            .to_string();
+
+        for (prefix, entry) in [("-", change.old), ("+", change.new)] {
+            if let Some(entry) = entry {
+                // raw values may span several lines
+                for line in pair(&name, &entry.value, opts).lines() {
+                    println!("{}{}", prefix, line);
+                }
+            }
+        }
+    }
+}
+
+fn print_json(changes: &[Change<Entry>], old_file: &str, new_file: &str) {
+    let entry = |entry: Option<&Entry>| match entry {
+        Some(entry) => serde_json::json!({
+            "value": entry.value,
+            "line": entry.line,
+            "column": entry.col,
+        }),
+        None => serde_json::Value::Null,
+    };
+
+    let changes = changes
+        .iter()
+        .map(|change| {
+            serde_json::json!({
+                "key": change.key,
+                "section": change.section(),
+                "kind": match change.kind() {
+                    ChangeKind::Added => "added",
+                    ChangeKind::Removed => "removed",
+                    ChangeKind::Changed => "changed",
+                },
+                "old": entry(change.old),
+                "new": entry(change.new),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{}",
+        serde_json::json!({
+            "old": old_file,
+            "new": new_file,
+            "changes": changes,
+        })
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pair() {
+        let opts = Opts::default();
+        assert_eq!(super::pair("a.b", "1", opts), "a.b = 1");
+        assert_eq!(super::pair("a..b", " 1", opts), "a..b = ` 1`");
+        // the key is written as read if the serializer rejects it
+        assert_eq!(super::pair("a b", " 1", opts), "a b = ` 1`");
+        assert_eq!(super::value("x`y", opts), "`x``y`");
+    }
+}
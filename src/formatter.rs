@@ -1,82 +1,51 @@
-use cni_format::CniExt;
+use crate::diff::pair;
+use crate::input::{self, Entry};
+use cni_format::{KeyPath, SerializerOptions};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
 
 pub enum Format {
-    /// The section threshold, i.e. how many items before a section heading
-    /// is used. Zero means no section headings should be used.
-    Cni(usize),
+    /// CNI format, written by the serializer of the library.
+    Cni(SerializerOptions),
+    /// CNI format without section headers, with the file and position each
+    /// value was read from in a comment. If `overrides` is true, earlier
+    /// definitions of a key that were overridden are listed in comments too.
+    Origin { overrides: bool },
     /// If the first is None, the key is not printed.
     /// If the second is None, the value is not printed.
     Custom(Option<String>, Option<String>, String),
 }
 
-fn print_cni(map: &HashMap<String, String>) {
-    // don't use cni_format::to_str so there are no section headings
-    for (key, value) in map {
-        print!("{} = ", key);
-        if value.is_empty() {
-            println!("#empty");
-        } else if value
-            .contains(|c| c == '`' || c == '#' || c == ';' || crate::linter::is_vertical_ws(&c))
-        {
-            println!("`{}`", value.replace("`", "``"));
-        } else {
-            println!("{}", value);
+pub fn format(files: clap::Values, format: Format, opts: cni_format::Opts) {
+    // all definitions of each key, the last one is the one that is used
+    let mut definitions = HashMap::<String, Vec<(&str, Entry)>>::new();
+    for file in files {
+        let read = input::read(file, opts, |key, entry| {
+            definitions.entry(key).or_default().push((file, entry));
+        });
+        if let Err(e) = read {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
     }
-}
-
-pub fn format(files: clap::Values, format: Format, opts: cni_format::Opts) {
-    let map = files
-        .flat_map(|file| {
-            let stream: Box<dyn Read> = if file == "-" {
-                Box::new(std::io::stdin())
-            } else {
-                match File::open(&file) {
-                    Ok(f) => Box::new(f),
-                    Err(e) => {
-                        eprintln!("{:?}: {}", file, e);
-                        std::process::exit(1);
-                    }
-                }
-            };
 
-            let stream =
-                utf::decode_utf8(stream.bytes().filter_map(Result::ok)).filter_map(Result::ok);
-            cni_format::CniParser::new_opts(stream, opts)
-        })
-        .collect::<Result<HashMap<_, _>, _>>();
-
-    if let Err(e) = map {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    if let Format::Origin { overrides } = format {
+        show_origin(definitions, overrides, opts);
+        return;
     }
-    let mut map = map.unwrap();
 
-    match format {
-        Format::Cni(0) => print_cni(&map),
-        Format::Cni(section_threshold) => {
-            // print the leaves in the top level
-            print_cni(&map.sub_leaves(""));
-            map.retain(|key, _| key.contains('.'));
+    let map = definitions
+        .into_iter()
+        .filter_map(|(key, mut definitions)| Some((key, definitions.pop()?.1.value)))
+        .collect::<HashMap<_, _>>();
 
-            let mut sections = map.section_tree("").iter().cloned().collect::<Vec<_>>();
-            sections.sort_unstable_by(|a, b|
-                // long before short, then alphabetically
-                a.len().cmp(&b.len()).reverse().then_with(|| a.cmp(b)));
-            for section in sections {
-                if map.sub_tree(&section).len() >= section_threshold {
-                    println!("[{}]", section);
-                    print_cni(&map.sub_tree(&section));
-                    map.retain(|key, _| !key.starts_with(&format!("{}.", section)));
-                }
+    match format {
+        Format::Cni(opts) => match cni_format::to_str_with(map, &opts) {
+            Ok(cni) => print!("{}", cni),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
             }
-
-            // print the remaining values
-            print_cni(&map);
-        }
+        },
         Format::Custom(pre, mid, post) => {
             for (key, value) in map {
                 if let Some(ref pre) = pre {
@@ -88,5 +57,59 @@ pub fn format(files: clap::Values, format: Format, opts: cni_format::Opts) {
                 print!("{}", post);
             }
         }
+        Format::Origin { .. } => unreachable!("handled above"),
+    }
+}
+
+/// Prints each key with the position of the value in a comment, like
+/// `key = value # file.cni:3:7`.
+fn show_origin(
+    definitions: HashMap<String, Vec<(&str, Entry)>>,
+    overrides: bool,
+    opts: cni_format::Opts,
+) {
+    let origin = |file: &str, entry: &Entry| {
+        format!(
+            "{}:{}:{}",
+            if file == "-" { "<stdin>" } else { file },
+            entry.line,
+            entry.col
+        )
+    };
+
+    let mut definitions = definitions.into_iter().collect::<Vec<_>>();
+    definitions.sort_by(|(a, _), (b, _)| {
+        let (a, b) = (KeyPath::from(a), KeyPath::from(b));
+        a.segments().cmp(b.segments())
+    });
+
+    for (key, mut definitions) in definitions {
+        let Some((file, entry)) = definitions.pop() else {
+            continue;
+        };
+        println!(
+            "{} # {}",
+            pair(&key, &entry.value, opts),
+            origin(file, &entry)
+        );
+
+        if overrides {
+            // most recently overridden first
+            for (file, entry) in definitions.iter().rev() {
+                let shadowed = format!(
+                    "{} # {}",
+                    pair(&key, &entry.value, opts),
+                    origin(file, entry)
+                );
+                // raw values may span several lines
+                for (i, line) in shadowed.lines().enumerate() {
+                    if i == 0 {
+                        println!("# overrides {}", line);
+                    } else {
+                        println!("#   {}", line);
+                    }
+                }
+            }
+        }
     }
 }
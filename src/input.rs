@@ -0,0 +1,59 @@
+use cni_format::{CniParser, Opts};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+/// A value read from a CNI file with the position it was read from.
+pub struct Entry {
+    pub value: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl PartialEq for Entry {
+    /// Entries are equal if their values are equal, the position is not
+    /// compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+/// Opens a file for reading, '-' means stdin.
+pub fn open(file: &str) -> Result<Box<dyn Read>, String> {
+    if file == "-" {
+        Ok(Box::new(std::io::stdin()))
+    } else {
+        File::open(file)
+            .map(|f| Box::new(f) as Box<dyn Read>)
+            .map_err(|e| format!("{:?}: {}", file, e))
+    }
+}
+
+/// Reads a CNI file and calls `f` with each key and the value with its
+/// position, in the order they appear in the file.
+pub fn read<F>(file: &str, opts: Opts, mut f: F) -> Result<(), String>
+where
+    F: FnMut(String, Entry),
+{
+    let stream = open(file)?;
+    let stream = utf::decode_utf8(BufReader::new(stream).bytes().filter_map(Result::ok))
+        .filter_map(Result::ok);
+    let mut parser = CniParser::new_opts(stream, opts);
+
+    while let Some(result) = parser.next() {
+        let (key, value) = result.map_err(|e| format!("{:?}: {}", file, e))?;
+        // the parser always knows the position after a successful read
+        let (line, col) = parser.last_pos().unwrap_or_default();
+        f(key, Entry { value, line, col });
+    }
+    Ok(())
+}
+
+/// Reads a CNI file and remembers where each value was read from.
+pub fn load(file: &str, opts: Opts) -> Result<HashMap<String, Entry>, String> {
+    let mut map = HashMap::new();
+    read(file, opts, |key, entry| {
+        map.insert(key, entry);
+    })?;
+    Ok(map)
+}
@@ -12,7 +12,7 @@ pub fn is_vertical_ws(c: &char) -> bool {
     )
 }
 
-fn is_key(c: &char, opts: &Opts) -> bool {
+pub fn is_key(c: &char, opts: &Opts) -> bool {
     if opts.more_keys {
         !(matches!(c, '[' | ']' | '=' | '`' | '#') || (opts.ini && *c == ';') || c.is_whitespace())
     } else {
@@ -20,7 +20,7 @@ fn is_key(c: &char, opts: &Opts) -> bool {
     }
 }
 
-fn is_value(c: &char, opts: &Opts) -> bool {
+pub fn is_value(c: &char, opts: &Opts) -> bool {
     !(*c == '#' || (opts.ini && *c == ';') || is_vertical_ws(c))
 }
 
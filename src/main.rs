@@ -1,9 +1,13 @@
 use clap::{crate_authors, crate_description, crate_version, App, AppSettings, Arg, SubCommand};
 use std::collections::HashMap;
 
+mod consistency;
+mod diff;
 mod formatter;
+mod input;
 mod iter;
 mod linter;
+mod merge;
 
 fn main() {
     let matches = App::new("cniutil")
@@ -63,6 +67,7 @@ fn main() {
                         .help("Can only be used with --cni. Specifies the threshold of how many entries have to be in a section to make use of a section header. 0 means no section headers will be used. [default: 10]")
                         .long("section-threshold")
                         .short("n")
+                        .takes_value(true)
                         .validator(|arg| arg.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
                         .requires("cni")
                 )
@@ -87,6 +92,18 @@ fn main() {
                         .long("format")
                         .takes_value(true)
                 )
+                .arg(
+                    Arg::with_name("show-origin")
+                        .help("Shows the file, line and column each value was read from in a comment. Section headers are not used.")
+                        .conflicts_with_all(&["csv", "null", "format", "threshold"])
+                        .long("show-origin")
+                )
+                .arg(
+                    Arg::with_name("show-overrides")
+                        .help("Also lists the earlier definitions of each key that were overridden by a later file or line, in comments. Implies --show-origin.")
+                        .conflicts_with_all(&["csv", "null", "format", "threshold"])
+                        .long("show-overrides")
+                )
                 .arg(
                     Arg::with_name("FILES")
                         .help("The input files to read. '-' will result in stdin being read.")
@@ -94,6 +111,111 @@ fn main() {
                         .default_value("-")
                 )
         )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .setting(AppSettings::UnifiedHelpMessage)
+                .about("Compares two CNI files by their keys and values, ignoring order and formatting.")
+                .after_help("The exit status is 0 if there are no differences, 1 if there are differences and 2 if a file could not be read.")
+                .arg(
+                    Arg::with_name("unified")
+                        .help("Shows removed and added entries as CNI, similar to a unified diff.")
+                        .overrides_with("json")
+                        .long("unified")
+                        .short("u")
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .help("Shows the differences as JSON.")
+                        .overrides_with("unified")
+                        .long("json")
+                )
+                .arg(
+                    Arg::with_name("ignore")
+                        .help("Ignores keys matching the pattern. In the pattern, '*' matches one part of a key and '**' matches any number of parts.")
+                        .long("ignore")
+                        .value_name("KEYPATTERN")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                )
+                .arg(
+                    Arg::with_name("OLD")
+                        .help("The original file. '-' will result in stdin being read.")
+                        .required(true)
+                )
+                .arg(
+                    Arg::with_name("NEW")
+                        .help("The changed file. '-' will result in stdin being read.")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("merge")
+                .setting(AppSettings::UnifiedHelpMessage)
+                .about("Merges the changes from BASE to THEIRS into OURS by keys, keeping the comments and layout of OURS.")
+                .after_help("Keys that were changed differently in OURS and THEIRS are marked as conflicts. Like git merge-file, the result is written to OURS and the exit status is 1 if there were conflicts and 2 if a file could not be read. To use this as a git merge driver, see the README.")
+                .arg(
+                    Arg::with_name("stdout")
+                        .help("Prints the result instead of writing it to OURS.")
+                        .long("stdout")
+                        .short("p")
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .help("Uses the label instead of the file name in conflict markers. Can be given up to three times for OURS, BASE and THEIRS, in that order.")
+                        .long("label")
+                        .short("L")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .max_values(3)
+                )
+                .arg(
+                    Arg::with_name("marker-size")
+                        .help("The number of characters in conflict markers. [default: 7]")
+                        .long("marker-size")
+                        .takes_value(true)
+                        .validator(|arg| arg.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                )
+                .arg(
+                    Arg::with_name("diff3")
+                        .help("Also shows the value from BASE in conflicts.")
+                        .long("diff3")
+                )
+                .arg(
+                    Arg::with_name("BASE")
+                        .help("The common ancestor of both versions. '-' will result in stdin being read.")
+                        .required(true)
+                )
+                .arg(
+                    Arg::with_name("OURS")
+                        .help("The current version, which is overwritten with the result.")
+                        .required(true)
+                )
+                .arg(
+                    Arg::with_name("THEIRS")
+                        .help("The version to merge. '-' will result in stdin being read.")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("check-consistency")
+                .setting(AppSettings::UnifiedHelpMessage)
+                .about("Compares the keys of several CNI files, e.g. the configurations of different environments.")
+                .after_help("Reports keys that are missing from some of the files, sections that only exist in one file and keys that have the same value in all files. The exit status is 0 if no keys are missing and no section only exists in one file, 1 if there are such differences and 2 if a file could not be read.")
+                .arg(
+                    Arg::with_name("json")
+                        .help("Shows the report as JSON.")
+                        .long("json")
+                )
+                .arg(
+                    Arg::with_name("FILES")
+                        .help("The input files to compare. '-' will result in stdin being read.")
+                        .multiple(true)
+                        .min_values(2)
+                        .required(true)
+                )
+        )
         .get_matches();
 
     // get enabled CNI extensions
@@ -148,7 +270,13 @@ fn main() {
         ("format", Some(matches)) => {
             use formatter::Format;
 
-            let format = if matches.is_present("csv") {
+            let show_origin =
+                matches.is_present("show-origin") || matches.is_present("show-overrides");
+            let format = if show_origin {
+                Format::Origin {
+                    overrides: matches.is_present("show-overrides"),
+                }
+            } else if matches.is_present("csv") {
                 Format::Custom(Some("".into()), Some(",\"".into()), "\"\n".into())
             } else if matches.is_present("null") {
                 Format::Custom(Some("".into()), Some("=".into()), "\0".into())
@@ -171,11 +299,100 @@ fn main() {
                     .unwrap_or("10")
                     .parse()
                     .unwrap();
-                Format::Cni(section_threshold)
+                Format::Cni(cni_format::SerializerOptions {
+                    headers: if section_threshold == 0 {
+                        cni_format::Headers::Never
+                    } else {
+                        cni_format::Headers::Threshold(section_threshold)
+                    },
+                    max_depth: usize::MAX,
+                    // the output can be parsed with the same options
+                    cni: opts,
+                    ..cni_format::SerializerOptions::default()
+                })
             };
 
             formatter::format(matches.values_of("FILES").unwrap(), format, opts);
         }
+        ("diff", Some(matches)) => {
+            use diff::Output;
+
+            let output = if matches.is_present("unified") {
+                Output::Unified
+            } else if matches.is_present("json") {
+                Output::Json
+            } else {
+                Output::Human
+            };
+            let ignore = matches
+                .values_of("ignore")
+                .map(|patterns| patterns.map(cni_format::Glob::new).collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            match diff::diff(
+                matches.value_of("OLD").unwrap(),
+                matches.value_of("NEW").unwrap(),
+                &ignore,
+                output,
+                opts,
+            ) {
+                Ok(false) => {}
+                Ok(true) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        ("merge", Some(matches)) => {
+            let (base, ours, theirs) = (
+                matches.value_of("BASE").unwrap(),
+                matches.value_of("OURS").unwrap(),
+                matches.value_of("THEIRS").unwrap(),
+            );
+            let mut labels = matches.values_of("label").into_iter().flatten();
+            let options = merge::Options {
+                labels: [
+                    labels.next().unwrap_or(ours).to_string(),
+                    labels.next().unwrap_or(base).to_string(),
+                    labels.next().unwrap_or(theirs).to_string(),
+                ],
+                // the unwrap is okay because of the validator in clap
+                marker_size: matches
+                    .value_of("marker-size")
+                    .unwrap_or("7")
+                    .parse()
+                    .unwrap(),
+                diff3: matches.is_present("diff3"),
+                stdout: matches.is_present("stdout"),
+            };
+
+            match merge::merge(base, ours, theirs, &options, opts) {
+                Ok(conflicts) if conflicts.is_empty() => {}
+                Ok(conflicts) => {
+                    for key in conflicts {
+                        eprintln!("conflict: {}", key);
+                    }
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        ("check-consistency", Some(matches)) => {
+            let files = matches.values_of("FILES").unwrap().collect::<Vec<_>>();
+
+            match consistency::check(&files, matches.is_present("json"), opts) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
         _ => unreachable!("unknown subcommand"),
     }
 }
@@ -0,0 +1,816 @@
+use crate::diff::{pair, value};
+use crate::input::{self, Entry};
+use crate::linter::{is_key, is_value, is_vertical_ws};
+use cni_format::{CniParser, KeyPath, Opts};
+use std::collections::HashMap;
+
+pub struct Options {
+    /// Labels for the conflict markers, in the order ours, base, theirs.
+    pub labels: [String; 3],
+    /// The number of characters in a conflict marker.
+    pub marker_size: usize,
+    /// Whether conflicts also show the value from the base file.
+    pub diff3: bool,
+    /// Whether the result is printed instead of written to the file of ours.
+    pub stdout: bool,
+}
+
+/// A key/value pair in the source of ours, with the byte offsets of its
+/// parts in the source.
+struct Span {
+    value: String,
+    /// The section header that is in effect for the pair.
+    section: String,
+    /// The order in which the pairs appear in the source.
+    order: usize,
+    line_start: usize,
+    key_start: usize,
+    value_start: usize,
+    value_end: usize,
+    /// The start of the line after the value.
+    next_line: usize,
+    /// Whether the pair is the only thing on its lines, not counting
+    /// whitespace and a comment after it.
+    whole_line: bool,
+}
+
+impl Span {
+    fn indent<'s>(&self, src: &'s str) -> &'s str {
+        let indent = &src[self.line_start..self.key_start];
+        if indent.trim().is_empty() {
+            indent
+        } else {
+            ""
+        }
+    }
+}
+
+/// Replaces the source between `start` and `end` with `text`.
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+impl Edit {
+    /// Whether the edit only changes the source between `start` and `end`.
+    /// Insertions at the boundaries are outside.
+    fn within(&self, start: usize, end: usize) -> bool {
+        if self.start == self.end {
+            start < self.start && self.start < end
+        } else {
+            start <= self.start && self.end <= end
+        }
+    }
+
+    /// Whether the edit changes some of the source between `start` and `end`
+    /// and some outside of it.
+    fn crosses(&self, start: usize, end: usize) -> bool {
+        self.start < end && start < self.end && !self.within(start, end)
+    }
+}
+
+/// Conflict markers around the lines of ours between `start` and `end`.
+/// Other edits within these lines are applied to the side of ours.
+struct Conflict {
+    start: usize,
+    end: usize,
+    /// The pairs of the base file, already formatted.
+    base: String,
+    /// The pairs of theirs, already formatted.
+    theirs: String,
+}
+
+struct Merge<'s> {
+    src: &'s str,
+    spans: HashMap<String, Span>,
+    opts: Opts,
+    options: &'s Options,
+    edits: Vec<Edit>,
+    conflicts: Vec<Conflict>,
+    /// Text that is appended after the source, for keys in sections that
+    /// are not in ours.
+    appended: String,
+    /// The section header in effect at the end of the output, if it is known.
+    eof_section: Option<String>,
+}
+
+/// Merges the changes from base to theirs into ours by keys. Returns the
+/// keys that could not be merged because they were changed in both.
+pub fn merge(
+    base_file: &str,
+    ours_file: &str,
+    theirs_file: &str,
+    options: &Options,
+    opts: Opts,
+) -> Result<Vec<String>, String> {
+    let base = input::load(base_file, opts)?;
+    let theirs = input::load(theirs_file, opts)?;
+    let src = std::fs::read_to_string(ours_file).map_err(|e| format!("{:?}: {}", ours_file, e))?;
+    let (result, conflicts) = merge_src(&src, &base, &theirs, options, opts)
+        .map_err(|e| format!("{:?}: {}", ours_file, e))?;
+
+    if options.stdout {
+        print!("{}", result);
+    } else {
+        std::fs::write(ours_file, result).map_err(|e| format!("{:?}: {}", ours_file, e))?;
+    }
+
+    Ok(conflicts)
+}
+
+/// Merges the changes from base to theirs into the source of ours. Returns
+/// the merged source and the keys that were changed in both.
+fn merge_src(
+    src: &str,
+    base: &HashMap<String, Entry>,
+    theirs: &HashMap<String, Entry>,
+    options: &Options,
+    opts: Opts,
+) -> Result<(String, Vec<String>), cni_format::error::Error> {
+    let spans = spans(src, opts)?;
+
+    let eof_section = {
+        let last = spans.values().max_by_key(|span| span.order);
+        let rest = &src[last.map_or(0, |span| span.next_line)..];
+        // there might be a section header without keys after the last pair
+        if rest.contains('[') {
+            None
+        } else {
+            Some(last.map(|span| span.section.clone()).unwrap_or_default())
+        }
+    };
+
+    let mut merge = Merge {
+        src,
+        spans,
+        opts,
+        options,
+        edits: Vec::new(),
+        conflicts: Vec::new(),
+        appended: String::new(),
+        eof_section,
+    };
+
+    let mut conflicts = Vec::new();
+    // only keys that were changed in theirs have to be looked at
+    for change in cni_format::diff(base, theirs) {
+        let ours = merge.spans.get(change.key).map(|span| &span.value);
+        let (base, theirs) = (change.old.map(|e| &e.value), change.new.map(|e| &e.value));
+
+        if ours == theirs {
+            // both made the same change
+        } else if ours == base {
+            merge.apply(change.key, change.new);
+        } else {
+            merge.conflict(change.key, change.old, change.new);
+            conflicts.push(change.key.to_string());
+        }
+    }
+
+    Ok((merge.finish(), conflicts))
+}
+
+/// Returns the offset at which the line that contains `offset` starts.
+fn line_start(src: &str, offset: usize) -> usize {
+    src[..offset]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| is_vertical_ws(c))
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// Returns the offset at which the line after `offset` starts, or the end of
+/// the source if there is none.
+fn next_line(src: &str, offset: usize) -> usize {
+    match src[offset..]
+        .char_indices()
+        .find(|(_, c)| is_vertical_ws(c))
+    {
+        Some((i, c)) => {
+            let next_line = offset + i + c.len_utf8();
+            // CRLF is one line break
+            if c == '\r' && src[next_line..].starts_with('\n') {
+                next_line + 1
+            } else {
+                next_line
+            }
+        }
+        None => src.len(),
+    }
+}
+
+/// Returns `offset` if it is at the start of a line, or the start of the
+/// next line otherwise.
+fn line_end(src: &str, offset: usize) -> usize {
+    if offset == 0 || src[..offset].ends_with(|c| is_vertical_ws(&c)) {
+        offset
+    } else {
+        next_line(src, offset)
+    }
+}
+
+/// Parses the source and finds where each key/value pair is located.
+fn spans(src: &str, opts: Opts) -> Result<HashMap<String, Span>, cni_format::error::Error> {
+    // the byte offsets at which lines start, counted the same way the parser
+    // counts lines
+    let mut lines = vec![0];
+    let mut chars = src.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let crlf = c == '\r' && matches!(chars.peek(), Some((_, '\n')));
+        if is_vertical_ws(&c) && !crlf {
+            lines.push(i + c.len_utf8());
+        }
+    }
+    let offset = |(line, col): (usize, usize)| {
+        let start = lines[line - 1];
+        src[start..]
+            .char_indices()
+            .nth(col - 1)
+            .map_or(src.len(), |(i, _)| start + i)
+    };
+
+    let mut spans = HashMap::new();
+    let mut parser = CniParser::new_opts(src.chars(), opts);
+    let mut order = 0;
+    while let Some(result) = parser.next() {
+        let (key, value) = result?;
+        // the parser always knows the position after a successful read
+        let value_start = offset(parser.last_pos().unwrap_or((1, 1)));
+
+        // go back over the equals sign to the key as it is written
+        let before = src[..value_start].trim_end();
+        let before = before.strip_suffix('=').unwrap_or(before).trim_end();
+        let key_start = before
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_key(c, &opts))
+            .last()
+            .map_or(before.len(), |(i, _)| i);
+        let written = &before[key_start..];
+        let section = if written.len() < key.len() {
+            key[..key.len() - written.len() - 1].to_string()
+        } else {
+            String::new()
+        };
+
+        let value_end = value_start + value_len(&src[value_start..], &opts);
+        let line_start = line_start(src, key_start);
+        let next_line = next_line(src, value_end);
+        // the rest of the line, without the line break
+        let tail = src[value_end..next_line].trim();
+        let whole_line = src[line_start..key_start].trim().is_empty()
+            && tail.chars().next().is_none_or(|c| !is_value(&c, &opts));
+
+        spans.insert(
+            key,
+            Span {
+                value,
+                section,
+                order,
+                line_start,
+                key_start,
+                value_start,
+                value_end,
+                next_line,
+                whole_line,
+            },
+        );
+        order += 1;
+    }
+    Ok(spans)
+}
+
+/// Returns the length of the value at the start of the source as it is
+/// written, including the backticks of raw values.
+fn value_len(src: &str, opts: &Opts) -> usize {
+    if let Some(raw) = src.strip_prefix('`') {
+        let mut chars = raw.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == '`' {
+                if matches!(chars.peek(), Some((_, '`'))) {
+                    // escaped backtick
+                    chars.next();
+                } else {
+                    return i + 2;
+                }
+            }
+        }
+        src.len()
+    } else {
+        let end = src.find(|c| !is_value(&c, opts)).unwrap_or(src.len());
+        src[..end].trim_end().len()
+    }
+}
+
+impl Merge<'_> {
+    /// Changes the key in ours to the value of theirs.
+    fn apply(&mut self, key: &str, theirs: Option<&Entry>) {
+        let edit = match (self.spans.get(key), theirs) {
+            (Some(span), Some(theirs)) => Edit {
+                start: span.value_start,
+                end: span.value_end,
+                text: value(&theirs.value, self.opts),
+            },
+            (Some(span), None) if span.whole_line => Edit {
+                start: span.line_start,
+                end: span.next_line,
+                text: String::new(),
+            },
+            (Some(span), None) => Edit {
+                start: span.key_start,
+                end: span.value_end,
+                text: String::new(),
+            },
+            (None, Some(theirs)) => {
+                let (start, indent, section) = self.location(key);
+                let name = KeyPath::from(key)
+                    .strip_prefix(&section)
+                    .unwrap_or(key)
+                    .to_string();
+                let text = format!("{}{}\n", indent, pair(&name, &theirs.value, self.opts));
+                self.insert(start, text);
+                return;
+            }
+            (None, None) => return,
+        };
+        self.edits.push(edit);
+    }
+
+    /// Writes conflict markers with the values of ours and theirs.
+    fn conflict(&mut self, key: &str, base: Option<&Entry>, theirs: Option<&Entry>) {
+        let (range, indent, section) = match self.spans.get(key) {
+            Some(span) => (
+                Some((span.line_start, span.next_line)),
+                span.indent(self.src).to_string(),
+                span.section.clone(),
+            ),
+            None => {
+                let (start, indent, section) = self.location(key);
+                (start.map(|start| (start, start)), indent, section)
+            }
+        };
+
+        let name = KeyPath::from(key)
+            .strip_prefix(&section)
+            .unwrap_or(key)
+            .to_string();
+        let side = |entry: Option<&Entry>| {
+            entry.map_or_else(String::new, |entry| {
+                format!("{}{}\n", indent, pair(&name, &entry.value, self.opts))
+            })
+        };
+        let (base, theirs) = (side(base), side(theirs));
+
+        match range {
+            Some((start, end)) => self.conflicts.push(Conflict {
+                start,
+                end,
+                base,
+                theirs,
+            }),
+            None => self.appended += &self.markers("", &base, &theirs),
+        }
+    }
+
+    /// Returns the lines of both sides of a conflict with conflict markers.
+    fn markers(&self, ours: &str, base: &str, theirs: &str) -> String {
+        let marker = |c: char, label: &str| {
+            let marker = c.to_string().repeat(self.options.marker_size);
+            if label.is_empty() {
+                format!("{}\n", marker)
+            } else {
+                format!("{} {}\n", marker, label)
+            }
+        };
+        let [ours_label, base_label, theirs_label] = &self.options.labels;
+
+        let mut text = marker('<', ours_label);
+        text += ours;
+        if !ours.is_empty() && !ours.ends_with(|c| is_vertical_ws(&c)) {
+            text.push('\n');
+        }
+        if self.options.diff3 {
+            text += &marker('|', base_label);
+            text += base;
+        }
+        text += &marker('=', "");
+        text += theirs;
+        text += &marker('>', theirs_label);
+        text
+    }
+
+    /// Inserts text at an offset of the source, or after the source.
+    fn insert(&mut self, start: Option<usize>, text: String) {
+        match start {
+            Some(start) => self.edits.push(Edit {
+                start,
+                end: start,
+                text,
+            }),
+            None => self.appended += &text,
+        }
+    }
+
+    /// Finds where a key that is not in ours can be inserted. Returns the
+    /// offset, the indentation and the section header that is in effect
+    /// there.
+    ///
+    /// The key is put after the last key that has the most parts in common
+    /// with it. If there is none, keys of the top level section are put at
+    /// the start of the source. Otherwise the offset is `None` and the key
+    /// has to be appended after the source, where a section header was added
+    /// for it.
+    fn location(&mut self, key: &str) -> (Option<usize>, String, String) {
+        let path = KeyPath::from(key);
+        // a key with empty parts like `a..b` can not be split next to them
+        let fits = |section: &str| {
+            !section.ends_with('.')
+                && path
+                    .strip_prefix(section)
+                    .is_some_and(|name| !name.starts_with('.'))
+        };
+        let mut parent = path.parent().unwrap_or_default();
+        while !fits(parent.as_str()) {
+            parent = parent.parent().unwrap_or_default().into_owned();
+        }
+        let section = parent.into_string();
+
+        let best = self
+            .spans
+            .iter()
+            .filter(|(_, span)| fits(&span.section))
+            .map(|(other, span)| {
+                let common = path
+                    .segments()
+                    .zip(KeyPath::from(other).segments())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (common, span)
+            })
+            .filter(|(common, span)| *common > 0 || section.is_empty() && span.section.is_empty())
+            .max_by_key(|(common, span)| (*common, span.order));
+
+        if let Some((_, span)) = best {
+            return (
+                Some(span.next_line),
+                span.indent(self.src).to_string(),
+                span.section.clone(),
+            );
+        }
+
+        if section.is_empty() {
+            return (Some(0), String::new(), section);
+        }
+        if self.eof_section.as_ref() != Some(&section) {
+            if !self.src.trim().is_empty() || !self.appended.is_empty() {
+                self.appended.push('\n');
+            }
+            self.appended += &format!("[{}]\n", section);
+            self.eof_section = Some(section.clone());
+        }
+        (None, String::new(), section)
+    }
+
+    /// Applies the edits and conflicts to the source.
+    fn finish(mut self) -> String {
+        // edits are sorted stably so insertions at the same offset keep their order
+        self.edits.sort_by_key(|edit| (edit.start, edit.end));
+        let conflicts = self.combine_conflicts();
+
+        let mut result = String::with_capacity(self.src.len() + self.appended.len());
+        let mut edits = self.edits.iter().peekable();
+        let mut pos = 0;
+        for conflict in &conflicts {
+            while let Some(edit) = edits.next_if(|edit| edit.end <= conflict.start) {
+                self.splice(&mut result, pos, edit);
+                pos = edit.end;
+            }
+            result += &self.src[pos..conflict.start];
+
+            // changes to other pairs on the same lines still apply to ours
+            let mut ours = String::new();
+            pos = conflict.start;
+            while let Some(edit) = edits.next_if(|edit| edit.within(conflict.start, conflict.end)) {
+                self.splice(&mut ours, pos, edit);
+                pos = edit.end;
+            }
+            ours += &self.src[pos..conflict.end];
+            pos = conflict.end;
+
+            result += &self.markers(&ours, &conflict.base, &conflict.theirs);
+        }
+        for edit in edits {
+            self.splice(&mut result, pos, edit);
+            pos = edit.end;
+        }
+        result += &self.src[pos..];
+
+        if !self.appended.is_empty() {
+            // lines can only be added after the source if it ends with a line break
+            if !result.is_empty() && !result.ends_with(|c| is_vertical_ws(&c)) {
+                result.push('\n');
+            }
+            result += &self.appended;
+        }
+        result
+    }
+
+    /// Copies the source from `pos` up to an edit and applies the edit.
+    fn splice(&self, result: &mut String, pos: usize, edit: &Edit) {
+        *result += &self.src[pos..edit.start];
+        // lines can only be inserted after the source if it ends with a line break
+        let last_line = edit.start == self.src.len() && edit.start == edit.end;
+        if last_line && !result.is_empty() && !result.ends_with(|c| is_vertical_ws(&c)) {
+            result.push('\n');
+        }
+        *result += &edit.text;
+    }
+
+    /// Sorts the conflicts and combines the ones that share lines, so that
+    /// every edit is either completely inside or outside of a conflict.
+    fn combine_conflicts(&mut self) -> Vec<Conflict> {
+        let mut conflicts = std::mem::take(&mut self.conflicts);
+        loop {
+            // conflicts with the same offset stay in the order they were added
+            conflicts.sort_by_key(|conflict| conflict.start);
+            let mut combined: Vec<Conflict> = Vec::with_capacity(conflicts.len());
+            for conflict in conflicts {
+                match combined.last_mut() {
+                    Some(last) if conflict.start < last.end => {
+                        last.end = last.end.max(conflict.end);
+                        last.base += &conflict.base;
+                        last.theirs += &conflict.theirs;
+                    }
+                    _ => combined.push(conflict),
+                }
+            }
+            conflicts = combined;
+
+            // e.g. a raw value that ends on the line of a conflict
+            let mut extended = false;
+            for conflict in &mut conflicts {
+                for edit in &self.edits {
+                    if edit.crosses(conflict.start, conflict.end) {
+                        conflict.start = line_start(self.src, conflict.start.min(edit.start));
+                        conflict.end = line_end(self.src, conflict.end.max(edit.end));
+                        extended = true;
+                    }
+                }
+            }
+            if !extended {
+                return conflicts;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(diff3: bool) -> Options {
+        Options {
+            labels: ["ours".into(), "base".into(), "theirs".into()],
+            marker_size: 7,
+            diff3,
+            stdout: true,
+        }
+    }
+
+    fn entries(src: &str) -> HashMap<String, Entry> {
+        CniParser::new(src.chars())
+            .map(|result| {
+                let (key, value) = result.unwrap();
+                (
+                    key,
+                    Entry {
+                        value,
+                        line: 0,
+                        col: 0,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn run(base: &str, ours: &str, theirs: &str) -> (String, Vec<String>) {
+        merge_src(
+            ours,
+            &entries(base),
+            &entries(theirs),
+            &options(false),
+            Opts::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn value_len() {
+        let opts = Opts::default();
+        assert_eq!(super::value_len("abc  # comment", &opts), 3);
+        assert_eq!(super::value_len("a b c\nd = 1", &opts), 5);
+        assert_eq!(super::value_len("`a``b` c = 1", &opts), 6);
+        assert_eq!(super::value_len("`a\nb`\n", &opts), 5);
+        assert_eq!(super::value_len("`unterminated", &opts), 13);
+        assert_eq!(super::value_len("", &opts), 0);
+    }
+
+    #[test]
+    fn spans() {
+        let src = "a = `1` b = `x\ny`\r\n[s]\n  c = 3 # c\nd.e = 4";
+        let spans = super::spans(src, Opts::default()).unwrap();
+        let range = |key: &str| {
+            let span = &spans[key];
+            (
+                &src[span.line_start..span.key_start],
+                &src[span.key_start..span.value_start],
+                &src[span.value_start..span.value_end],
+                &src[span.value_end..span.next_line],
+            )
+        };
+
+        // pairs that share a line
+        assert_eq!(range("a"), ("", "a = ", "`1`", " b = `x\n"));
+        assert!(!spans["a"].whole_line);
+        assert_eq!(range("b"), ("a = `1` ", "b = ", "`x\ny`", "\r\n"));
+        assert_eq!(spans["b"].indent(src), "");
+        assert_eq!(spans["b"].value, "x\ny");
+        assert!(!spans["b"].whole_line);
+
+        assert_eq!(range("s.c"), ("  ", "c = ", "3", " # c\n"));
+        assert_eq!(spans["s.c"].section, "s");
+        assert_eq!(spans["s.c"].indent(src), "  ");
+        assert!(spans["s.c"].whole_line);
+
+        // no line break at the end
+        assert_eq!(range("s.d.e"), ("", "d.e = ", "4", ""));
+        assert_eq!(spans["s.d.e"].section, "s");
+        assert_eq!(spans["s.d.e"].next_line, src.len());
+        assert_eq!(spans["s.d.e"].order, 3);
+    }
+
+    #[test]
+    fn location() {
+        let src = "a = 1\n[s]\n  b = 2\n[t.u]\nc = 3\n";
+        let options = options(false);
+        let mut merge = Merge {
+            src,
+            spans: super::spans(src, Opts::default()).unwrap(),
+            opts: Opts::default(),
+            options: &options,
+            edits: Vec::new(),
+            conflicts: Vec::new(),
+            appended: String::new(),
+            eof_section: Some("t.u".into()),
+        };
+
+        // after the pair with the most parts in common
+        assert_eq!(merge.location("x"), (Some(6), String::new(), String::new()));
+        assert_eq!(merge.location("s.x"), (Some(18), "  ".into(), "s".into()));
+        assert_eq!(merge.location("s.y.z"), (Some(18), "  ".into(), "s".into()));
+        assert_eq!(
+            merge.location("t.u.x"),
+            (Some(30), String::new(), "t.u".into())
+        );
+        assert!(merge.appended.is_empty());
+
+        // a section that is not in ours gets a header
+        assert_eq!(merge.location("t.x"), (None, String::new(), "t".into()));
+        assert_eq!(merge.location("v.w"), (None, String::new(), "v".into()));
+        assert_eq!(merge.location("v.x"), (None, String::new(), "v".into()));
+        assert_eq!(merge.appended, "\n[t]\n\n[v]\n");
+    }
+
+    #[test]
+    fn location_top_level() {
+        let src = "# comment\n[s]\nb = 2\n";
+        let options = options(false);
+        let mut merge = Merge {
+            src,
+            spans: super::spans(src, Opts::default()).unwrap(),
+            opts: Opts::default(),
+            options: &options,
+            edits: Vec::new(),
+            conflicts: Vec::new(),
+            appended: String::new(),
+            eof_section: Some("s".into()),
+        };
+
+        // there is no `[]` header for the top level section
+        assert_eq!(merge.location("x"), (Some(0), String::new(), String::new()));
+        assert!(merge.appended.is_empty());
+    }
+
+    #[test]
+    fn location_empty_parts() {
+        let src = "x = 1\n[a]\nc = 2\n";
+        let options = options(false);
+        let mut merge = Merge {
+            src,
+            spans: super::spans(src, Opts::default()).unwrap(),
+            opts: Opts::default(),
+            options: &options,
+            edits: Vec::new(),
+            conflicts: Vec::new(),
+            appended: String::new(),
+            eof_section: Some("a".into()),
+        };
+
+        // keys are not split next to an empty part
+        assert_eq!(
+            merge.location("a..b"),
+            (Some(6), String::new(), String::new())
+        );
+        assert_eq!(
+            merge.location("a.c..d"),
+            (Some(16), String::new(), "a".into())
+        );
+        assert_eq!(merge.location("v.w..y"), (None, String::new(), "v".into()));
+        assert_eq!(merge.appended, "\n[v]\n");
+
+        let (merged, conflicts) = run("x = 1\n", "x = 1\n", "x = 1\na..b = 2\n");
+        assert_eq!((merged.as_str(), conflicts), ("x = 1\na..b = 2\n", vec![]));
+        assert!(cni_format::from_str(&merged).is_ok());
+    }
+
+    #[test]
+    fn finish() {
+        // changes, removals and additions
+        assert_eq!(
+            run(
+                "a = 1\nb = 2\nc = 3\n",
+                "a = 1 # one\nb = 2\nc = 3\n",
+                "a = 4\nc = 3\nd = 5\n",
+            ),
+            ("a = 4 # one\nc = 3\nd = 5\n".into(), vec![])
+        );
+
+        // raw values and CRLF line breaks
+        assert_eq!(
+            run(
+                "a = `x\ny`\r\nb = 1\r\n",
+                "a = `x\ny`\r\nb = 1\r\n",
+                "a = `z`\nb = 1\n",
+            ),
+            ("a = z\r\nb = 1\r\n".into(), vec![])
+        );
+        assert_eq!(
+            run("a = 1\r\nb = 2\r\n", "a = 1\r\nb = 2\r\n", "b = 2\n"),
+            ("b = 2\r\n".into(), vec![])
+        );
+
+        // no line break at the end
+        assert_eq!(
+            run("a = 1", "a = 1", "a = 1\nb = 2\n"),
+            ("a = 1\nb = 2\n".into(), vec![])
+        );
+        assert_eq!(
+            run("a = 1", "a = 2", "a = 3\n"),
+            (
+                "<<<<<<< ours\na = 2\n=======\na = 3\n>>>>>>> theirs\n".into(),
+                vec!["a".into()]
+            )
+        );
+
+        // new top level keys
+        assert_eq!(
+            run("[s]\nb = 2\n", "[s]\nb = 2\n", "a = 1\n[s]\nb = 2\n"),
+            ("a = 1\n[s]\nb = 2\n".into(), vec![])
+        );
+        assert_eq!(
+            run("", "", "a = 1\nb.c = 2\n"),
+            ("a = 1\n[b]\nc = 2\n".into(), vec![])
+        );
+    }
+
+    #[test]
+    fn shared_lines() {
+        // a change to another pair on the line of a conflict goes to ours
+        assert_eq!(
+            run("a = `x` b = 1\n", "a = `y` b = 1\n", "a = `z` b = 2\n"),
+            (
+                "<<<<<<< ours\na = `y` b = 2\n=======\na = z\n>>>>>>> theirs\n".into(),
+                vec!["a".into()]
+            )
+        );
+
+        // conflicts on the same line are combined
+        assert_eq!(
+            run("a = `1` b = 1\n", "a = `2` b = 2\n", "a = `3` b = 3\n"),
+            (
+                "<<<<<<< ours\na = `2` b = 2\n=======\na = 3\nb = 3\n>>>>>>> theirs\n".into(),
+                vec!["a".into(), "b".into()]
+            )
+        );
+
+        // a raw value that ends on the line of a conflict
+        assert_eq!(
+            run("a = `x\ny` b = 1\n", "a = `x\ny` b = 2\n", "b = 3\n"),
+            (
+                "<<<<<<< ours\n b = 2\n=======\nb = 3\n>>>>>>> theirs\n".into(),
+                vec!["b".into()]
+            )
+        );
+    }
+}